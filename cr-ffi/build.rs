@@ -0,0 +1,27 @@
+//! Generates the C header from the `extern "C"` functions in `src/lib.rs`
+//!
+//! The header is written to `OUT_DIR`, and the test `header` checks that it matches the
+//! committed `include/cr.h`. With `CR_FFI_UPDATE_HEADER` set, `include/cr.h` is overwritten as
+//! well.
+
+use std::env;
+use std::path::{Path, PathBuf};
+
+fn main() {
+    let crate_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+    let crate_dir = Path::new(&crate_dir);
+    let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml"))
+        .expect("invalid cbindgen.toml");
+    let bindings =
+        cbindgen::generate_with_config(crate_dir, config).expect("failed to generate the C header");
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    bindings.write_to_file(out_dir.join("cr.h"));
+    if env::var_os("CR_FFI_UPDATE_HEADER").is_some() {
+        bindings.write_to_file(crate_dir.join("include/cr.h"));
+    }
+
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-env-changed=CR_FFI_UPDATE_HEADER");
+}
@@ -0,0 +1,123 @@
+/*
+ * C interface of the cr crate
+ *
+ * Link against libcr_ffi (static or shared). Ciphers and hash functions are selected by name,
+ * e.g. "aes128", "aes192", "aes256", "des", "3des", "desx", "twofish", "md5" and "sha1"; names
+ * are matched case-insensitively. All contexts must be released with the corresponding *_free
+ * function, which wipes the key material and hash state.
+ *
+ * Generated from src/lib.rs by cbindgen, do not edit.
+ */
+
+#ifndef CR_H
+#define CR_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+// Returned on success
+#define CR_OK 0
+
+// Returned for null pointers and buffers of the wrong size
+#define CR_ERROR_INVALID_ARGUMENT -1
+
+// Keyed block cipher context
+typedef struct CrCipher CrCipher;
+
+// Streaming hash context
+typedef struct CrHash CrHash;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Creates a cipher context, e.g. for `"aes128"`, `"des"` or `"twofish"`
+//
+// Returns null if the name is unknown or the key size is not supported.
+//
+// # Safety
+//
+// `name` must be a null-terminated string and `key` must point to `key_len` readable bytes.
+CrCipher *cr_cipher_new(const char *name, const uint8_t *key, size_t key_len);
+
+// Returns the block size of the cipher in bytes, or 0 if `cipher` is null
+//
+// # Safety
+//
+// `cipher` must be null or a context returned by `cr_cipher_new`.
+size_t cr_cipher_block_size(const CrCipher *cipher);
+
+// Encrypts a single block in place
+//
+// Returns `CR_ERROR_INVALID_ARGUMENT` if `block_len` is not the block size of the cipher.
+//
+// # Safety
+//
+// `cipher` must be a context returned by `cr_cipher_new` and `block` must point to `block_len`
+// writable bytes.
+int cr_cipher_encrypt(const CrCipher *cipher, uint8_t *block, size_t block_len);
+
+// Decrypts a single block in place
+//
+// Returns `CR_ERROR_INVALID_ARGUMENT` if `block_len` is not the block size of the cipher.
+//
+// # Safety
+//
+// `cipher` must be a context returned by `cr_cipher_new` and `block` must point to `block_len`
+// writable bytes.
+int cr_cipher_decrypt(const CrCipher *cipher, uint8_t *block, size_t block_len);
+
+// Wipes and releases the cipher context; null is ignored
+//
+// # Safety
+//
+// `cipher` must be null or a context returned by `cr_cipher_new` which was not released yet.
+void cr_cipher_free(CrCipher *cipher);
+
+// Creates a hash context, e.g. for `"md5"` or `"sha1"`
+//
+// Returns null if the name is unknown.
+//
+// # Safety
+//
+// `name` must be a null-terminated string.
+CrHash *cr_hash_new(const char *name);
+
+// Returns the output size of the hash function in bytes, or 0 if `hash` is null
+//
+// # Safety
+//
+// `hash` must be null or a context returned by `cr_hash_new`.
+size_t cr_hash_output_size(const CrHash *hash);
+
+// Absorbs `input` into the hash state
+//
+// # Safety
+//
+// `hash` must be a context returned by `cr_hash_new` and `input` must point to `input_len`
+// readable bytes.
+int cr_hash_update(CrHash *hash, const uint8_t *input, size_t input_len);
+
+// Writes the hash to `out` and resets the context for hashing a new message
+//
+// Returns `CR_ERROR_INVALID_ARGUMENT` if `out_len` is smaller than the output size; the
+// context is left unchanged in this case.
+//
+// # Safety
+//
+// `hash` must be a context returned by `cr_hash_new` and `out` must point to `out_len`
+// writable bytes.
+int cr_hash_finalize(CrHash *hash, uint8_t *out, size_t out_len);
+
+// Wipes and releases the hash context; null is ignored
+//
+// # Safety
+//
+// `hash` must be null or a context returned by `cr_hash_new` which was not released yet.
+void cr_hash_free(CrHash *hash);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* CR_H */
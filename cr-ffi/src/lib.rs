@@ -0,0 +1,301 @@
+//! C interface of `cr`
+//!
+//! The library is built as `cdylib` and `staticlib`; the declarations in `include/cr.h` are
+//! generated from this file by cbindgen and updated with `CR_FFI_UPDATE_HEADER=1 cargo build`.
+//! Ciphers and hash functions are selected by their name in [`cr::registry`]. All contexts are
+//! heap allocated, must be released with the corresponding `*_free` function, and wipe their key
+//! material and state when released.
+
+use cr::registry::{self, DynBlockCipher, DynHasher};
+
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int};
+use std::ptr;
+use std::slice;
+
+/// Returned on success
+pub const CR_OK: c_int = 0;
+/// Returned for null pointers and buffers of the wrong size
+pub const CR_ERROR_INVALID_ARGUMENT: c_int = -1;
+
+/// Keyed block cipher context
+pub struct CrCipher(Box<dyn DynBlockCipher>);
+
+/// Streaming hash context
+pub struct CrHash(Box<dyn DynHasher>);
+
+/// Creates a cipher context, e.g. for `"aes128"`, `"des"` or `"twofish"`
+///
+/// Returns null if the name is unknown or the key size is not supported.
+///
+/// # Safety
+///
+/// `name` must be a null-terminated string and `key` must point to `key_len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn cr_cipher_new(
+    name: *const c_char,
+    key: *const u8,
+    key_len: usize,
+) -> *mut CrCipher {
+    let name = match name_from_ptr(name) {
+        Some(name) => name,
+        None => return ptr::null_mut(),
+    };
+    if key.is_null() && key_len != 0 {
+        return ptr::null_mut();
+    }
+    let key = if key_len == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(key, key_len)
+    };
+    match registry::new_cipher(name, key) {
+        Ok(cipher) => Box::into_raw(Box::new(CrCipher(cipher))),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Returns the block size of the cipher in bytes, or 0 if `cipher` is null
+///
+/// # Safety
+///
+/// `cipher` must be null or a context returned by `cr_cipher_new`.
+#[no_mangle]
+pub unsafe extern "C" fn cr_cipher_block_size(cipher: *const CrCipher) -> usize {
+    if cipher.is_null() {
+        return 0;
+    }
+    (*cipher).0.block_size()
+}
+
+/// Encrypts a single block in place
+///
+/// Returns `CR_ERROR_INVALID_ARGUMENT` if `block_len` is not the block size of the cipher.
+///
+/// # Safety
+///
+/// `cipher` must be a context returned by `cr_cipher_new` and `block` must point to `block_len`
+/// writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn cr_cipher_encrypt(
+    cipher: *const CrCipher,
+    block: *mut u8,
+    block_len: usize,
+) -> c_int {
+    match block_from_ptr(cipher, block, block_len) {
+        Some((cipher, block)) => {
+            cipher.encrypt_block(block);
+            CR_OK
+        }
+        None => CR_ERROR_INVALID_ARGUMENT,
+    }
+}
+
+/// Decrypts a single block in place
+///
+/// Returns `CR_ERROR_INVALID_ARGUMENT` if `block_len` is not the block size of the cipher.
+///
+/// # Safety
+///
+/// `cipher` must be a context returned by `cr_cipher_new` and `block` must point to `block_len`
+/// writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn cr_cipher_decrypt(
+    cipher: *const CrCipher,
+    block: *mut u8,
+    block_len: usize,
+) -> c_int {
+    match block_from_ptr(cipher, block, block_len) {
+        Some((cipher, block)) => {
+            cipher.decrypt_block(block);
+            CR_OK
+        }
+        None => CR_ERROR_INVALID_ARGUMENT,
+    }
+}
+
+/// Wipes and releases the cipher context; null is ignored
+///
+/// # Safety
+///
+/// `cipher` must be null or a context returned by `cr_cipher_new` which was not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn cr_cipher_free(cipher: *mut CrCipher) {
+    if !cipher.is_null() {
+        drop(Box::from_raw(cipher));
+    }
+}
+
+/// Creates a hash context, e.g. for `"md5"` or `"sha1"`
+///
+/// Returns null if the name is unknown.
+///
+/// # Safety
+///
+/// `name` must be a null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn cr_hash_new(name: *const c_char) -> *mut CrHash {
+    match name_from_ptr(name).map(registry::new_hash) {
+        Some(Ok(hasher)) => Box::into_raw(Box::new(CrHash(hasher))),
+        _ => ptr::null_mut(),
+    }
+}
+
+/// Returns the output size of the hash function in bytes, or 0 if `hash` is null
+///
+/// # Safety
+///
+/// `hash` must be null or a context returned by `cr_hash_new`.
+#[no_mangle]
+pub unsafe extern "C" fn cr_hash_output_size(hash: *const CrHash) -> usize {
+    if hash.is_null() {
+        return 0;
+    }
+    (*hash).0.output_size()
+}
+
+/// Absorbs `input` into the hash state
+///
+/// # Safety
+///
+/// `hash` must be a context returned by `cr_hash_new` and `input` must point to `input_len`
+/// readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn cr_hash_update(
+    hash: *mut CrHash,
+    input: *const u8,
+    input_len: usize,
+) -> c_int {
+    if hash.is_null() || (input.is_null() && input_len != 0) {
+        return CR_ERROR_INVALID_ARGUMENT;
+    }
+    if input_len != 0 {
+        (*hash).0.update(slice::from_raw_parts(input, input_len));
+    }
+    CR_OK
+}
+
+/// Writes the hash to `out` and resets the context for hashing a new message
+///
+/// Returns `CR_ERROR_INVALID_ARGUMENT` if `out_len` is smaller than the output size; the
+/// context is left unchanged in this case.
+///
+/// # Safety
+///
+/// `hash` must be a context returned by `cr_hash_new` and `out` must point to `out_len`
+/// writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn cr_hash_finalize(
+    hash: *mut CrHash,
+    out: *mut u8,
+    out_len: usize,
+) -> c_int {
+    if hash.is_null() || out.is_null() {
+        return CR_ERROR_INVALID_ARGUMENT;
+    }
+    let hasher = &mut (*hash).0;
+    let output_size = hasher.output_size();
+    if out_len < output_size {
+        return CR_ERROR_INVALID_ARGUMENT;
+    }
+    let digest = hasher.finalize_reset();
+    slice::from_raw_parts_mut(out, output_size).copy_from_slice(digest.as_ref());
+    CR_OK
+}
+
+/// Wipes and releases the hash context; null is ignored
+///
+/// # Safety
+///
+/// `hash` must be null or a context returned by `cr_hash_new` which was not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn cr_hash_free(hash: *mut CrHash) {
+    if !hash.is_null() {
+        drop(Box::from_raw(hash));
+    }
+}
+
+unsafe fn name_from_ptr<'a>(name: *const c_char) -> Option<&'a str> {
+    if name.is_null() {
+        return None;
+    }
+    CStr::from_ptr(name).to_str().ok()
+}
+
+unsafe fn block_from_ptr<'a>(
+    cipher: *const CrCipher,
+    block: *mut u8,
+    block_len: usize,
+) -> Option<(&'a dyn DynBlockCipher, &'a mut [u8])> {
+    if cipher.is_null() || block.is_null() {
+        return None;
+    }
+    let cipher = &*(*cipher).0;
+    if block_len != cipher.block_size() {
+        return None;
+    }
+    Some((cipher, slice::from_raw_parts_mut(block, block_len)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cipher() {
+        unsafe {
+            let key = [0u8; 16];
+            let cipher = cr_cipher_new(b"AES128\0".as_ptr() as *const c_char, key.as_ptr(), 16);
+            assert!(!cipher.is_null());
+            assert_eq!(cr_cipher_block_size(cipher), 16);
+
+            let mut block = [0u8; 16];
+            assert_eq!(cr_cipher_encrypt(cipher, block.as_mut_ptr(), 16), CR_OK);
+            assert_eq!(
+                block,
+                [
+                    0x66, 0xE9, 0x4B, 0xD4, 0xEF, 0x8A, 0x2C, 0x3B, 0x88, 0x4C, 0xFA, 0x59, 0xCA,
+                    0x34, 0x2B, 0x2E
+                ]
+            );
+            assert_eq!(
+                cr_cipher_decrypt(cipher, block.as_mut_ptr(), 8),
+                CR_ERROR_INVALID_ARGUMENT
+            );
+            assert_eq!(cr_cipher_decrypt(cipher, block.as_mut_ptr(), 16), CR_OK);
+            assert_eq!(block, [0; 16]);
+            cr_cipher_free(cipher);
+
+            let des = b"des\0".as_ptr() as *const c_char;
+            assert!(cr_cipher_new(des, key.as_ptr(), 16).is_null());
+            assert!(cr_cipher_new(ptr::null(), key.as_ptr(), 8).is_null());
+            assert_eq!(cr_cipher_block_size(ptr::null()), 0);
+            cr_cipher_free(ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn test_hash() {
+        unsafe {
+            let hash = cr_hash_new(b"md5\0".as_ptr() as *const c_char);
+            assert!(!hash.is_null());
+            assert_eq!(cr_hash_output_size(hash), 16);
+
+            assert_eq!(cr_hash_update(hash, b"abc".as_ptr(), 3), CR_OK);
+            let mut out = [0u8; 16];
+            assert_eq!(
+                cr_hash_finalize(hash, out.as_mut_ptr(), 15),
+                CR_ERROR_INVALID_ARGUMENT
+            );
+            assert_eq!(cr_hash_finalize(hash, out.as_mut_ptr(), 16), CR_OK);
+            assert_eq!(cr::md5::md5(b"abc"), out);
+            assert_eq!(cr_hash_update(hash, ptr::null(), 0), CR_OK);
+            assert_eq!(cr_hash_finalize(hash, out.as_mut_ptr(), 16), CR_OK);
+            assert_eq!(cr::md5::md5(b""), out);
+            cr_hash_free(hash);
+
+            assert!(cr_hash_new(b"sha256\0".as_ptr() as *const c_char).is_null());
+            assert_eq!(cr_hash_output_size(ptr::null()), 0);
+        }
+    }
+}
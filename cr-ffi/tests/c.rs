@@ -0,0 +1,40 @@
+//! Compiles `tests/test.c` against the static library and runs it
+//!
+//! Requires a C compiler, which is taken from `CC` or defaults to `cc`.
+
+#![cfg(unix)]
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Directory of the library artifacts, i.e. the parent of the `deps` directory of this test
+fn artifact_dir() -> PathBuf {
+    let exe = env::current_exe().unwrap();
+    exe.parent().and_then(Path::parent).unwrap().to_path_buf()
+}
+
+#[test]
+fn test_c_program() {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let staticlib = artifact_dir().join("libcr_ffi.a");
+    assert!(staticlib.exists(), "{} not found", staticlib.display());
+    let program = Path::new(env!("CARGO_TARGET_TMPDIR")).join("cr_ffi_test");
+
+    let cc = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    let status = Command::new(cc)
+        .args(["-std=c99", "-Wall", "-Wextra", "-Werror"])
+        .arg("-I")
+        .arg(manifest_dir.join("include"))
+        .arg(manifest_dir.join("tests/test.c"))
+        .arg(&staticlib)
+        .args(["-lpthread", "-ldl", "-lm"])
+        .arg("-o")
+        .arg(&program)
+        .status()
+        .expect("failed to run the C compiler");
+    assert!(status.success(), "compiling test.c failed");
+
+    let status = Command::new(&program).status().unwrap();
+    assert!(status.success(), "test.c failed");
+}
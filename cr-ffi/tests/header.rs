@@ -0,0 +1,11 @@
+//! Checks that the committed `include/cr.h` matches the header generated from `src/lib.rs`
+
+#[test]
+fn test_header_is_up_to_date() {
+    let generated = include_str!(concat!(env!("OUT_DIR"), "/cr.h"));
+    let committed = include_str!("../include/cr.h");
+    assert!(
+        generated == committed,
+        "include/cr.h is out of date, regenerate it with `CR_FFI_UPDATE_HEADER=1 cargo build -p cr-ffi`"
+    );
+}
@@ -0,0 +1,119 @@
+#include "cr.h"
+
+#include <stdio.h>
+#include <string.h>
+
+static int failures = 0;
+
+#define CHECK(cond)                                                        \
+    do {                                                                   \
+        if (!(cond)) {                                                     \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__,         \
+                    __LINE__, #cond);                                      \
+            failures++;                                                    \
+        }                                                                  \
+    } while (0)
+
+static void test_cipher(const char *name, const uint8_t *key, size_t key_len,
+                        const uint8_t *plaintext, const uint8_t *ciphertext,
+                        size_t block_len) {
+    uint8_t block[16];
+    CrCipher *cipher = cr_cipher_new(name, key, key_len);
+    CHECK(cipher != NULL);
+    if (cipher == NULL) {
+        return;
+    }
+    CHECK(cr_cipher_block_size(cipher) == block_len);
+
+    memcpy(block, plaintext, block_len);
+    CHECK(cr_cipher_encrypt(cipher, block, block_len) == CR_OK);
+    CHECK(memcmp(block, ciphertext, block_len) == 0);
+    CHECK(cr_cipher_decrypt(cipher, block, block_len) == CR_OK);
+    CHECK(memcmp(block, plaintext, block_len) == 0);
+    CHECK(cr_cipher_encrypt(cipher, block, block_len - 1) ==
+          CR_ERROR_INVALID_ARGUMENT);
+
+    cr_cipher_free(cipher);
+}
+
+static void test_hash(const char *name, const char *input,
+                      const uint8_t *expected, size_t output_size) {
+    uint8_t out[20];
+    CrHash *hash = cr_hash_new(name);
+    CHECK(hash != NULL);
+    if (hash == NULL) {
+        return;
+    }
+    CHECK(cr_hash_output_size(hash) == output_size);
+
+    /* feed the input in two parts to exercise streaming */
+    size_t half = strlen(input) / 2;
+    CHECK(cr_hash_update(hash, (const uint8_t *)input, half) == CR_OK);
+    CHECK(cr_hash_update(hash, (const uint8_t *)input + half,
+                         strlen(input) - half) == CR_OK);
+    CHECK(cr_hash_finalize(hash, out, output_size - 1) ==
+          CR_ERROR_INVALID_ARGUMENT);
+    CHECK(cr_hash_finalize(hash, out, sizeof(out)) == CR_OK);
+    CHECK(memcmp(out, expected, output_size) == 0);
+
+    /* the context is reset after finalizing */
+    CHECK(cr_hash_update(hash, (const uint8_t *)input, strlen(input)) == CR_OK);
+    CHECK(cr_hash_finalize(hash, out, sizeof(out)) == CR_OK);
+    CHECK(memcmp(out, expected, output_size) == 0);
+
+    cr_hash_free(hash);
+}
+
+int main(void) {
+    static const uint8_t aes_key[16] = {0x00, 0x01, 0x02, 0x03, 0x04, 0x05,
+                                        0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b,
+                                        0x0c, 0x0d, 0x0e, 0x0f};
+    static const uint8_t aes_plaintext[16] = {
+        0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77,
+        0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff};
+    static const uint8_t aes_ciphertext[16] = {
+        0x69, 0xc4, 0xe0, 0xd8, 0x6a, 0x7b, 0x04, 0x30,
+        0xd8, 0xcd, 0xb7, 0x80, 0x70, 0xb4, 0xc5, 0x5a};
+    test_cipher("aes128", aes_key, sizeof(aes_key), aes_plaintext,
+                aes_ciphertext, 16);
+
+    static const uint8_t des_key[8] = {0x13, 0x34, 0x57, 0x79,
+                                       0x9b, 0xbc, 0xdf, 0xf1};
+    static const uint8_t des_plaintext[8] = {0x01, 0x23, 0x45, 0x67,
+                                             0x89, 0xab, 0xcd, 0xef};
+    static const uint8_t des_ciphertext[8] = {0x85, 0xe8, 0x13, 0x54,
+                                              0x0f, 0x0a, 0xb4, 0x05};
+    test_cipher("DES", des_key, sizeof(des_key), des_plaintext, des_ciphertext,
+                8);
+
+    static const uint8_t twofish_key[16] = {0};
+    static const uint8_t twofish_plaintext[16] = {0};
+    static const uint8_t twofish_ciphertext[16] = {
+        0x9f, 0x58, 0x9f, 0x5c, 0xf6, 0x12, 0x2c, 0x32,
+        0xb6, 0xbf, 0xec, 0x2f, 0x2a, 0xe8, 0xc3, 0x5a};
+    test_cipher("twofish", twofish_key, sizeof(twofish_key),
+                twofish_plaintext, twofish_ciphertext, 16);
+
+    CHECK(cr_cipher_new("des", aes_key, sizeof(aes_key)) == NULL);
+    CHECK(cr_cipher_new("rot13", aes_key, sizeof(aes_key)) == NULL);
+    cr_cipher_free(NULL);
+
+    static const uint8_t md5_abc[16] = {0x90, 0x01, 0x50, 0x98, 0x3c, 0xd2,
+                                        0x4f, 0xb0, 0xd6, 0x96, 0x3f, 0x7d,
+                                        0x28, 0xe1, 0x7f, 0x72};
+    test_hash("md5", "abc", md5_abc, 16);
+
+    static const uint8_t sha1_abc[20] = {
+        0xa9, 0x99, 0x3e, 0x36, 0x47, 0x06, 0x81, 0x6a, 0xba, 0x3e,
+        0x25, 0x71, 0x78, 0x50, 0xc2, 0x6c, 0x9c, 0xd0, 0xd8, 0x9d};
+    test_hash("sha1", "abc", sha1_abc, 20);
+
+    CHECK(cr_hash_new("sha256") == NULL);
+    cr_hash_free(NULL);
+
+    if (failures != 0) {
+        fprintf(stderr, "%d checks failed\n", failures);
+        return 1;
+    }
+    return 0;
+}
@@ -0,0 +1,37 @@
+//! Links `cr` into a freestanding binary
+//!
+//! Building the library only produces an rlib, which is never linked. For a target without an
+//! operating system, this binary has its own entry point and panic handler, so that linking
+//! fails if `cr` pulls in `std` or an allocator. On other targets it is an empty program.
+
+#![cfg_attr(target_os = "none", no_std)]
+#![cfg_attr(target_os = "none", no_main)]
+
+#[cfg(target_os = "none")]
+mod freestanding {
+    use core::hint::black_box;
+    use core::panic::PanicInfo;
+
+    #[no_mangle]
+    pub extern "C" fn _start() -> ! {
+        let mut block = black_box([0; 16]);
+        cr_no_std::aes128_encrypt(&mut block, &black_box([0; 16]));
+        black_box(block);
+        black_box(cr_no_std::des_encrypt(black_box([0; 8]), black_box([0; 8])));
+        black_box(cr_no_std::twofish_encrypt(
+            black_box([0; 16]),
+            &black_box([0; 32]),
+        ));
+        black_box(cr_no_std::md5(&black_box([0; 64])));
+        black_box(cr_no_std::sha1(&black_box([0; 64])));
+        loop {}
+    }
+
+    #[panic_handler]
+    fn panic(_: &PanicInfo) -> ! {
+        loop {}
+    }
+}
+
+#[cfg(not(target_os = "none"))]
+fn main() {}
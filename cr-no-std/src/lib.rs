@@ -0,0 +1,84 @@
+//! Links `cr` without the standard library
+//!
+//! This crate is `no_std` and depends on `cr` without default features, so building it for a
+//! target without `std` fails if `cr` uses anything but `core`:
+//!
+//! ```shell
+//! cargo build -p cr-no-std --target thumbv7em-none-eabihf
+//! ```
+//!
+//! The build includes the freestanding binary `cr-no-std-link`, which also fails to link if `cr`
+//! allocates.
+
+#![no_std]
+
+use cr::ct::Secret;
+use cr::BlockCipher;
+
+pub fn aes128_encrypt(block: &mut [u8; 16], key: &[u8; 16]) {
+    cr::aes::Aes128::new(*key).encrypt_block(block);
+}
+
+pub fn des_encrypt(block: [u8; 8], key: [u8; 8]) -> [u8; 8] {
+    cr::des::encrypt(block, key)
+}
+
+pub fn twofish_encrypt(block: [u8; 16], key: &[u8]) -> Option<[u8; 16]> {
+    cr::twofish::Twofish::from_slice(key)
+        .ok()
+        .map(|cipher| cipher.encrypt(block))
+}
+
+pub fn md5(input: &[u8]) -> Secret<[u8; 16]> {
+    cr::md5::md5(input)
+}
+
+pub fn sha1(input: &[u8]) -> Secret<[u8; 20]> {
+    cr::sha1::sha1(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_primitives() {
+        let mut block = [0; 16];
+        aes128_encrypt(&mut block, &[0; 16]);
+        assert_eq!(
+            block,
+            [
+                0x66, 0xE9, 0x4B, 0xD4, 0xEF, 0x8A, 0x2C, 0x3B, 0x88, 0x4C, 0xFA, 0x59, 0xCA, 0x34,
+                0x2B, 0x2E
+            ]
+        );
+        assert_eq!(
+            des_encrypt(
+                [0x01, 0x23, 0x45, 0x67, 0x89, 0xAB, 0xCD, 0xEF],
+                [0x13, 0x34, 0x57, 0x79, 0x9B, 0xBC, 0xDF, 0xF1]
+            ),
+            [0x85, 0xE8, 0x13, 0x54, 0x0F, 0x0A, 0xB4, 0x05]
+        );
+        assert_eq!(
+            twofish_encrypt([0; 16], &[]),
+            Some([
+                0x9F, 0x58, 0x9F, 0x5C, 0xF6, 0x12, 0x2C, 0x32, 0xB6, 0xBF, 0xEC, 0x2F, 0x2A, 0xE8,
+                0xC3, 0x5A
+            ])
+        );
+        assert_eq!(
+            md5(b""),
+            [
+                0xD4, 0x1D, 0x8C, 0xD9, 0x8F, 0x00, 0xB2, 0x04, 0xE9, 0x80, 0x09, 0x98, 0xEC, 0xF8,
+                0x42, 0x7E
+            ]
+        );
+        assert_eq!(
+            sha1(b"abc"),
+            [
+                0xA9, 0x99, 0x3E, 0x36, 0x47, 0x06, 0x81, 0x6A, 0xBA, 0x3E, 0x25, 0x71, 0x78, 0x50,
+                0xC2, 0x6C, 0x9C, 0xD0, 0xD8, 0x9D
+            ]
+        );
+    }
+}
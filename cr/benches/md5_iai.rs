@@ -1,10 +1,10 @@
 use iai::black_box;
 
-fn iai_md5_0000() -> [u8; 16] {
+fn iai_md5_0000() -> cr::ct::Secret<[u8; 16]> {
     cr::md5::md5(black_box(&[]))
 }
 
-fn iai_md5_1000() -> [u8; 16] {
+fn iai_md5_1000() -> cr::ct::Secret<[u8; 16]> {
     cr::md5::md5(black_box(&vec![0xffu8; 1000]))
 }
 
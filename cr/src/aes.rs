@@ -5,28 +5,119 @@
 // Note: all non-byte array types values (e.g. u32/u64) represent bytes in LE order.
 
 // AES-128: Nk = 4, Nb = 4, Nr = 10
+// AES-192: Nk = 6, Nb = 4, Nr = 12
+// AES-256: Nk = 8, Nb = 4, Nr = 14
 //
 
-const NK: usize = 4; // Key size in words
+use crate::zeroize::Zeroize;
+use crate::BlockCipher;
+
+use core::convert::TryInto;
+
 const NB: usize = 4; // Block size in words
-const NR: usize = 10; // Number of rounds
 
-pub fn encrypt_128(plaintext: [u8; 4 * NB], key: [u8; 4 * NK]) -> [u8; 4 * NB] {
-    let round_keys = key_expansion(key);
-    encrypt_impl(plaintext, round_keys)
+const NK_128: usize = 4; // Key size in words
+const NR_128: usize = 10; // Number of rounds
+const NK_192: usize = 6;
+const NR_192: usize = 12;
+const NK_256: usize = 8;
+const NR_256: usize = 14;
+
+pub fn encrypt_128(plaintext: [u8; 4 * NB], key: [u8; 4 * NK_128]) -> [u8; 4 * NB] {
+    Aes128::new(key).encrypt(plaintext)
+}
+
+pub fn decrypt_128(plaintext: [u8; 4 * NB], key: [u8; 4 * NK_128]) -> [u8; 4 * NB] {
+    Aes128::new(key).decrypt(plaintext)
+}
+
+pub fn encrypt_192(plaintext: [u8; 4 * NB], key: [u8; 4 * NK_192]) -> [u8; 4 * NB] {
+    Aes192::new(key).encrypt(plaintext)
+}
+
+pub fn decrypt_192(ciphertext: [u8; 4 * NB], key: [u8; 4 * NK_192]) -> [u8; 4 * NB] {
+    Aes192::new(key).decrypt(ciphertext)
 }
 
-pub fn decrypt_128(plaintext: [u8; 4 * NB], key: [u8; 4 * NK]) -> [u8; 4 * NB] {
-    let round_keys = key_expansion(key);
-    decrypt_impl(plaintext, round_keys)
+pub fn encrypt_256(plaintext: [u8; 4 * NB], key: [u8; 4 * NK_256]) -> [u8; 4 * NB] {
+    Aes256::new(key).encrypt(plaintext)
 }
 
-fn encrypt_impl(plaintext: [u8; 4 * NB], round_keys: [u32; NB * (NR + 1)]) -> [u8; 4 * NB] {
+pub fn decrypt_256(ciphertext: [u8; 4 * NB], key: [u8; 4 * NK_256]) -> [u8; 4 * NB] {
+    Aes256::new(key).decrypt(ciphertext)
+}
+
+macro_rules! aes {
+    ($name:ident, $nk:expr, $nr:expr, $doc:expr) => {
+        #[doc = $doc]
+        ///
+        /// The key is expanded once on construction, after that any number of blocks can be
+        /// encrypted or decrypted. The round keys are wiped on drop.
+        pub struct $name {
+            round_keys: [u32; NB * ($nr + 1)],
+        }
+
+        impl $name {
+            pub fn new(mut key: [u8; 4 * $nk]) -> Self {
+                let round_keys = key_expansion(&key);
+                key.zeroize();
+                Self { round_keys }
+            }
+
+            pub fn encrypt(&self, plaintext: [u8; 4 * NB]) -> [u8; 4 * NB] {
+                encrypt_impl(plaintext, &self.round_keys)
+            }
+
+            pub fn decrypt(&self, ciphertext: [u8; 4 * NB]) -> [u8; 4 * NB] {
+                decrypt_impl(ciphertext, &self.round_keys)
+            }
+        }
+
+        impl Zeroize for $name {
+            fn zeroize(&mut self) {
+                self.round_keys.zeroize();
+            }
+        }
+
+        impl Drop for $name {
+            fn drop(&mut self) {
+                self.zeroize();
+            }
+        }
+
+        impl BlockCipher for $name {
+            const BLOCK_SIZE: usize = 4 * NB;
+            const KEY_SIZE: usize = 4 * $nk;
+
+            fn new(key: &[u8]) -> Self {
+                $name::new(key.try_into().expect("invalid key size"))
+            }
+
+            fn encrypt_block(&self, block: &mut [u8]) {
+                let ciphertext = self.encrypt((&*block).try_into().expect("invalid block size"));
+                block.copy_from_slice(&ciphertext);
+            }
+
+            fn decrypt_block(&self, block: &mut [u8]) {
+                let plaintext = self.decrypt((&*block).try_into().expect("invalid block size"));
+                block.copy_from_slice(&plaintext);
+            }
+        }
+    };
+}
+
+aes!(Aes128, NK_128, NR_128, "AES-128 block cipher");
+aes!(Aes192, NK_192, NR_192, "AES-192 block cipher");
+aes!(Aes256, NK_256, NR_256, "AES-256 block cipher");
+
+/// Encrypts a block with `Nr = round_keys.len() / Nb - 1` rounds
+fn encrypt_impl(plaintext: [u8; 4 * NB], round_keys: &[u32]) -> [u8; 4 * NB] {
+    let nr = round_keys.len() / NB - 1;
     let mut state = plaintext;
 
     add_round_key(&mut state, &round_keys[0..NB]);
 
-    for round in 1..NR {
+    for round in 1..nr {
         sub_bytes(&mut state);
         shift_rows(&mut state);
         mix_columns(&mut state);
@@ -35,17 +126,19 @@ fn encrypt_impl(plaintext: [u8; 4 * NB], round_keys: [u32; NB * (NR + 1)]) -> [u
 
     sub_bytes(&mut state);
     shift_rows(&mut state);
-    add_round_key(&mut state, &round_keys[NR * NB..(NR + 1) * NB]);
+    add_round_key(&mut state, &round_keys[nr * NB..(nr + 1) * NB]);
 
     state
 }
 
-fn decrypt_impl(plaintext: [u8; 4 * NB], round_keys: [u32; NB * (NR + 1)]) -> [u8; 4 * NB] {
-    let mut state = plaintext;
+/// Decrypts a block with `Nr = round_keys.len() / Nb - 1` rounds
+fn decrypt_impl(ciphertext: [u8; 4 * NB], round_keys: &[u32]) -> [u8; 4 * NB] {
+    let nr = round_keys.len() / NB - 1;
+    let mut state = ciphertext;
 
-    add_round_key(&mut state, &round_keys[NR * NB..(NR + 1) * NB]);
+    add_round_key(&mut state, &round_keys[nr * NB..(nr + 1) * NB]);
 
-    for round in (1..NR).rev() {
+    for round in (1..nr).rev() {
         inv_shift_rows(&mut state);
         inv_sub_bytes(&mut state);
         add_round_key(&mut state, &round_keys[round * NB..(round + 1) * NB]);
@@ -188,22 +281,24 @@ fn rot_word(w: u32) -> u32 {
     w.rotate_right(8)
 }
 
-fn key_expansion(key: [u8; 4 * NK]) -> [u32; NB * (NR + 1)] {
-    let mut res = [0; NB * (NR + 1)];
+/// Expands `key` of `Nk = key.len() / 4` words into `N = Nb * (Nr + 1)` round keys
+fn key_expansion<const N: usize>(key: &[u8]) -> [u32; N] {
+    let nk = key.len() / 4;
+    let mut res = [0; N];
 
-    for i in 0..NK {
+    for i in 0..nk {
         res[i] = u32::from_le_bytes([key[4 * i], key[4 * i + 1], key[4 * i + 2], key[4 * i + 3]]);
     }
 
     let mut tmp;
-    for i in NK..NB * (NR + 1) {
+    for i in nk..N {
         tmp = res[i - 1];
-        if i % NK == 0 {
-            tmp = sub_word(rot_word(tmp)) ^ RCON[i / NK];
-        } else if NK > 6 && i % NK == 4 {
+        if i % nk == 0 {
+            tmp = sub_word(rot_word(tmp)) ^ RCON[i / nk];
+        } else if nk > 6 && i % nk == 4 {
             tmp = sub_word(tmp);
         }
-        res[i] = res[i - NK] ^ tmp;
+        res[i] = res[i - nk] ^ tmp;
     }
 
     res
@@ -399,6 +494,38 @@ mod tests {
         assert_eq!(decrypted, plaintext);
     }
 
+    #[test]
+    fn test_block_cipher_128() {
+        let key: [u8; 16] = hex("2b7e151628aed2a6abf7158809cf4f3c").unwrap();
+        let cipher = <Aes128 as BlockCipher>::new(&key);
+
+        let mut block: [u8; 16] = hex("3243f6a8885a308d313198a2e0370734").unwrap();
+        cipher.encrypt_block(&mut block);
+        assert_eq!(block, hex("3925841d02dc09fbdc118597196a0b32").unwrap());
+
+        cipher.decrypt_block(&mut block);
+        assert_eq!(block, hex("3243f6a8885a308d313198a2e0370734").unwrap());
+    }
+
+    #[test]
+    fn test_keyed_128_multiple_blocks() {
+        let cipher = Aes128::new(hex("000102030405060708090a0b0c0d0e0f").unwrap());
+        let plaintext = hex("00112233445566778899aabbccddeeff").unwrap();
+        let expected_ciphertext = hex("69c4e0d86a7b0430d8cdb78070b4c55a").unwrap();
+        for _ in 0..3 {
+            let ciphertext = cipher.encrypt(plaintext);
+            assert_eq!(ciphertext, expected_ciphertext);
+            assert_eq!(cipher.decrypt(ciphertext), plaintext);
+        }
+    }
+
+    #[test]
+    fn test_zeroize() {
+        let mut cipher = Aes256::new([0xff; 32]);
+        cipher.zeroize();
+        assert_eq!(cipher.round_keys, [0; NB * (NR_256 + 1)]);
+    }
+
     #[quickcheck]
     fn test_encrypt_decrypt_128_random(plaintext: u128, key: u128) {
         let plaintext = plaintext.to_le_bytes();
@@ -408,6 +535,51 @@ mod tests {
         assert_eq!(decrypted, plaintext);
     }
 
+    #[test]
+    fn test_encrypt_decrypt_appendix_c() {
+        let plaintext = hex("00112233445566778899aabbccddeeff").unwrap();
+
+        // C.1 AES-128
+        let key = hex("000102030405060708090a0b0c0d0e0f").unwrap();
+        let ciphertext = encrypt_128(plaintext, key);
+        assert_eq!(ciphertext, hex("69c4e0d86a7b0430d8cdb78070b4c55a").unwrap());
+        assert_eq!(decrypt_128(ciphertext, key), plaintext);
+
+        // C.2 AES-192
+        let key = hex("000102030405060708090a0b0c0d0e0f1011121314151617").unwrap();
+        let ciphertext = encrypt_192(plaintext, key);
+        assert_eq!(ciphertext, hex("dda97ca4864cdfe06eaf70a0ec0d7191").unwrap());
+        assert_eq!(decrypt_192(ciphertext, key), plaintext);
+
+        // C.3 AES-256
+        let key = hex("000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f").unwrap();
+        let ciphertext = encrypt_256(plaintext, key);
+        assert_eq!(ciphertext, hex("8ea2b7ca516745bfeafc49904b496089").unwrap());
+        assert_eq!(decrypt_256(ciphertext, key), plaintext);
+    }
+
+    #[quickcheck]
+    fn test_encrypt_decrypt_192_random(plaintext: u128, key0: u128, key1: u64) {
+        let plaintext = plaintext.to_le_bytes();
+        let mut key = [0; 24];
+        key[..16].copy_from_slice(&key0.to_le_bytes());
+        key[16..].copy_from_slice(&key1.to_le_bytes());
+
+        let decrypted = decrypt_192(encrypt_192(plaintext, key), key);
+        assert_eq!(decrypted, plaintext);
+    }
+
+    #[quickcheck]
+    fn test_encrypt_decrypt_256_random(plaintext: u128, key0: u128, key1: u128) {
+        let plaintext = plaintext.to_le_bytes();
+        let mut key = [0; 32];
+        key[..16].copy_from_slice(&key0.to_le_bytes());
+        key[16..].copy_from_slice(&key1.to_le_bytes());
+
+        let decrypted = decrypt_256(encrypt_256(plaintext, key), key);
+        assert_eq!(decrypted, plaintext);
+    }
+
     #[test]
     fn test_shift_rows() {
         let mut state = [
@@ -428,7 +600,7 @@ mod tests {
 
     #[test]
     fn test_key_expansion_128() {
-        const EXPECTED_ROUND_KEYS_BE: [u32; NB * (NR + 1)] = [
+        const EXPECTED_ROUND_KEYS_BE: [u32; NB * (NR_128 + 1)] = [
             0x2b7e1516, 0x28aed2a6, 0xabf71588, 0x09cf4f3c, 0xa0fafe17, 0x88542cb1, 0x23a33939,
             0x2a6c7605, 0xf2c295f2, 0x7a96b943, 0x5935807a, 0x7359f67f, 0x3d80477d, 0x4716fe3e,
             0x1e237e44, 0x6d7a883b, 0xef44a541, 0xa8525b7f, 0xb671253b, 0xdb0bad00, 0xd4d1c6f8,
@@ -442,11 +614,31 @@ mod tests {
             .map(|x| x.swap_bytes())
             .collect();
 
-        let key = hex("2b7e151628aed2a6abf7158809cf4f3c").unwrap();
-        let round_keys = key_expansion(key);
+        let key: [u8; 16] = hex("2b7e151628aed2a6abf7158809cf4f3c").unwrap();
+        let round_keys: [u32; NB * (NR_128 + 1)] = key_expansion(&key);
         assert_eq!(round_keys, &expected_round_keys[..]);
     }
 
+    #[test]
+    fn test_key_expansion_192() {
+        // A.2: first and last round keys
+        let key: [u8; 24] = hex("8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b").unwrap();
+        let round_keys: [u32; NB * (NR_192 + 1)] = key_expansion(&key);
+        assert_eq!(round_keys[6], 0xfe0c91f7u32.swap_bytes());
+        assert_eq!(round_keys[51], 0x01002202u32.swap_bytes());
+    }
+
+    #[test]
+    fn test_key_expansion_256() {
+        // A.3: first and last round keys
+        let key: [u8; 32] =
+            hex("603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4").unwrap();
+        let round_keys: [u32; NB * (NR_256 + 1)] = key_expansion(&key);
+        assert_eq!(round_keys[8], 0x9ba35411u32.swap_bytes());
+        assert_eq!(round_keys[12], 0xa8b09c1au32.swap_bytes());
+        assert_eq!(round_keys[59], 0x706c631eu32.swap_bytes());
+    }
+
     #[test]
     fn test_mix_columns() {
         let expected = hex("046681e5e0cb199a48f8d37a2806264c").unwrap();
@@ -0,0 +1,290 @@
+//! Constant-time comparison and selection
+//!
+//! Comparing digests, MACs or keys with `==` on arrays returns as soon as the first byte
+//! differs, which leaks the length of the common prefix through the timing. The functions of
+//! this module inspect all bytes and do not branch on their values. Results are represented by
+//! [`Choice`], which is converted to `bool` only at the very end.
+//!
+//! Digests and keys returned by the crate are wrapped in [`Secret`], which compares in constant
+//! time and does not print its value. Only the `const fn` digests `md5_const` and `sha1_const`
+//! return plain arrays, so that they can be evaluated at compile time.
+
+use crate::zeroize::Zeroize;
+
+use core::fmt;
+use core::ops;
+
+/// Boolean which is either 0 or 1 and is combined without branches
+#[derive(Clone, Copy, Debug)]
+pub struct Choice(u8);
+
+impl Choice {
+    /// Returns the choice as `0` or `1`
+    pub fn unwrap_u8(self) -> u8 {
+        self.0
+    }
+}
+
+impl From<bool> for Choice {
+    fn from(b: bool) -> Self {
+        Choice(b as u8)
+    }
+}
+
+impl From<Choice> for bool {
+    fn from(choice: Choice) -> bool {
+        // the barrier prevents the compiler from turning the preceding computation into an
+        // early return
+        core::hint::black_box(choice.0) != 0
+    }
+}
+
+impl ops::BitAnd for Choice {
+    type Output = Choice;
+
+    fn bitand(self, rhs: Choice) -> Choice {
+        Choice(self.0 & rhs.0)
+    }
+}
+
+impl ops::BitOr for Choice {
+    type Output = Choice;
+
+    fn bitor(self, rhs: Choice) -> Choice {
+        Choice(self.0 | rhs.0)
+    }
+}
+
+impl ops::Not for Choice {
+    type Output = Choice;
+
+    fn not(self) -> Choice {
+        Choice(self.0 ^ 1)
+    }
+}
+
+/// Equality which takes the same time for all values of the same length
+pub trait ConstantTimeEq {
+    fn ct_eq(&self, other: &Self) -> Choice;
+}
+
+/// Ordering which takes the same time for all values
+pub trait ConstantTimeLess {
+    fn ct_lt(&self, other: &Self) -> Choice;
+}
+
+/// Selection between two values without branching on the choice
+pub trait ConditionallySelectable: Sized {
+    /// Returns `a` if `choice` is 0 and `b` if `choice` is 1
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self;
+
+    /// Assigns `other` to `self` if `choice` is 1
+    fn conditional_assign(&mut self, other: &Self, choice: Choice) {
+        *self = Self::conditional_select(self, other, choice);
+    }
+}
+
+macro_rules! impl_ct_for_int {
+    ($($ty:ty),*) => {
+        $(
+            impl ConstantTimeEq for $ty {
+                fn ct_eq(&self, other: &Self) -> Choice {
+                    let x = self ^ other;
+                    // the top bit of `x | -x` is set iff `x` is not zero
+                    let ne = (x | x.wrapping_neg()) >> (<$ty>::BITS - 1);
+                    !Choice(ne as u8)
+                }
+            }
+
+            impl ConstantTimeLess for $ty {
+                fn ct_lt(&self, other: &Self) -> Choice {
+                    let (a, b) = (*self, *other);
+                    // the top bit is the borrow of `a - b`
+                    let borrow = (!a & b) | (!(a ^ b) & a.wrapping_sub(b));
+                    Choice((borrow >> (<$ty>::BITS - 1)) as u8)
+                }
+            }
+
+            impl ConditionallySelectable for $ty {
+                fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+                    let mask = (choice.0 as $ty).wrapping_neg();
+                    a ^ (mask & (a ^ b))
+                }
+            }
+        )*
+    };
+}
+
+impl_ct_for_int!(u8, u32, u64, usize);
+
+/// Slices of different length are unequal; the length is not considered secret.
+impl<T: ConstantTimeEq> ConstantTimeEq for [T] {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        if self.len() != other.len() {
+            return Choice(0);
+        }
+        self.iter()
+            .zip(other)
+            .fold(Choice(1), |acc, (a, b)| acc & a.ct_eq(b))
+    }
+}
+
+impl<T: ConstantTimeEq, const N: usize> ConstantTimeEq for [T; N] {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self[..].ct_eq(&other[..])
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ConstantTimeEq> ConstantTimeEq for Vec<T> {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self[..].ct_eq(&other[..])
+    }
+}
+
+impl<T: ConditionallySelectable + Copy, const N: usize> ConditionallySelectable for [T; N] {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        let mut out = *a;
+        for (x, y) in out.iter_mut().zip(b) {
+            x.conditional_assign(y, choice);
+        }
+        out
+    }
+}
+
+/// Compares two byte slices in constant time
+pub fn eq(a: &[u8], b: &[u8]) -> bool {
+    a.ct_eq(b).into()
+}
+
+/// Returns `a` if `choice` is false and `b` otherwise, without branching on `choice`
+pub fn select<T: ConditionallySelectable>(choice: bool, a: &T, b: &T) -> T {
+    T::conditional_select(a, b, choice.into())
+}
+
+/// Secret value, e.g. a digest or a key
+///
+/// The value is compared in constant time, is not printed by `Debug`, and is wiped on drop.
+/// Use [`Secret::expose`] to access it.
+pub struct Secret<T: Zeroize>(T);
+
+impl<T: Zeroize> Secret<T> {
+    pub const fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    /// Returns a reference to the secret value
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Zeroize> From<T> for Secret<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T: Zeroize + Clone> Clone for Secret<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T: Zeroize> fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Secret([REDACTED])")
+    }
+}
+
+impl<T: Zeroize + ConstantTimeEq> ConstantTimeEq for Secret<T> {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
+}
+
+impl<T: Zeroize + ConstantTimeEq> PartialEq for Secret<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl<T: Zeroize + ConstantTimeEq> Eq for Secret<T> {}
+
+impl<T: Zeroize + ConstantTimeEq> PartialEq<T> for Secret<T> {
+    fn eq(&self, other: &T) -> bool {
+        self.0.ct_eq(other).into()
+    }
+}
+
+impl<T: Zeroize + AsRef<[u8]>> AsRef<[u8]> for Secret<T> {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_ref()
+    }
+}
+
+impl<T: Zeroize> Drop for Secret<T> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use quickcheck_macros::quickcheck;
+
+    #[quickcheck]
+    fn test_eq(a: Vec<u8>, b: Vec<u8>) -> bool {
+        eq(&a, &b) == (a == b) && eq(&a, &a)
+    }
+
+    #[quickcheck]
+    fn test_int_eq(a: u64, b: u64) -> bool {
+        bool::from(a.ct_eq(&b)) == (a == b)
+            && bool::from((a as u32).ct_eq(&(b as u32))) == (a as u32 == b as u32)
+    }
+
+    #[quickcheck]
+    fn test_int_lt(a: u64, b: u8) -> bool {
+        bool::from(a.ct_lt(&(b as u64))) == (a < b as u64)
+            && bool::from((a as u8).ct_lt(&b)) == ((a as u8) < b)
+            && !bool::from(a.ct_lt(&a))
+    }
+
+    #[quickcheck]
+    fn test_select(a: u32, b: u32) -> bool {
+        select(false, &a, &b) == a && select(true, &a, &b) == b
+    }
+
+    #[test]
+    fn test_choice() {
+        let t = Choice::from(true);
+        let f = Choice::from(false);
+        assert!(bool::from(t & t));
+        assert!(!bool::from(t & f));
+        assert!(bool::from(t | f));
+        assert!(bool::from(!f));
+        assert_eq!(t.unwrap_u8(), 1);
+    }
+
+    #[test]
+    fn test_select_array() {
+        let mut a = [1_u8, 2, 3];
+        a.conditional_assign(&[4, 5, 6], Choice::from(false));
+        assert_eq!(a, [1, 2, 3]);
+        a.conditional_assign(&[4, 5, 6], Choice::from(true));
+        assert_eq!(a, [4, 5, 6]);
+    }
+
+    #[test]
+    fn test_secret() {
+        let secret = Secret::new([1_u8, 2, 3]);
+        assert_eq!(secret, [1, 2, 3]);
+        assert_ne!(secret, [1, 2, 4]);
+        assert_eq!(secret.clone(), secret);
+        assert_eq!(secret.as_ref(), &[1, 2, 3]);
+        assert_eq!(format!("{:?}", secret), "Secret([REDACTED])");
+    }
+}
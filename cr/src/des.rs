@@ -1,69 +1,384 @@
 //! Data Encryption Standard (DES) block cipher
 //!
 //! https://csrc.nist.gov/csrc/media/publications/fips/46/3/archive/1999-10-25/documents/fips46-3.pdf
+//!
+//! Blocks and keys are byte arrays in the order of FIPS 46-3, i.e. bit 1 is the most significant
+//! bit of the first byte, so that published test vectors can be used as is. The `*_u64`
+//! alternatives take the same blocks as big-endian `u64` values.
+
+use crate::ct::Secret;
+use crate::zeroize::Zeroize;
+use crate::BlockCipher;
+
+use core::convert::TryInto;
+use core::fmt;
+
+pub fn encrypt(plaintext: [u8; 8], key: [u8; 8]) -> [u8; 8] {
+    Des::new(key).encrypt(plaintext)
+}
+
+pub fn decrypt(ciphertext: [u8; 8], key: [u8; 8]) -> [u8; 8] {
+    Des::new(key).decrypt(ciphertext)
+}
+
+pub fn encrypt_u64(plaintext: u64, key: u64) -> u64 {
+    Des::new(key.to_be_bytes()).encrypt_u64(plaintext)
+}
+
+pub fn decrypt_u64(ciphertext: u64, key: u64) -> u64 {
+    Des::new(key.to_be_bytes()).decrypt_u64(ciphertext)
+}
+
+/// DES block cipher
+///
+/// The round keys are generated once on construction, after that any number of blocks can be
+/// encrypted or decrypted. The round keys are wiped on drop.
+pub struct Des {
+    round_keys: [u64; 16],
+    inv_round_keys: [u64; 16],
+}
+
+impl Des {
+    pub fn new(mut key: [u8; 8]) -> Self {
+        let mut round_keys = [0; 16];
+        for (dst, round_key) in round_keys
+            .iter_mut()
+            .zip(self::round_keys(u64::from_be_bytes(key)))
+        {
+            *dst = round_key;
+        }
+        key.zeroize();
+        let mut inv_round_keys = round_keys;
+        inv_round_keys.reverse();
+        Self {
+            round_keys,
+            inv_round_keys,
+        }
+    }
+
+    /// Creates the cipher, rejecting the 4 weak and 12 semi-weak keys
+    ///
+    /// Possibly weak keys are accepted, as in FIPS 74 and SP 800-67; use [`weak_key_class`] to
+    /// detect them.
+    pub fn new_strict(key: [u8; 8]) -> Result<Self, WeakKey> {
+        check_strict(key)?;
+        Ok(Self::new(key))
+    }
+
+    pub fn encrypt(&self, plaintext: [u8; 8]) -> [u8; 8] {
+        self.encrypt_u64(u64::from_be_bytes(plaintext))
+            .to_be_bytes()
+    }
+
+    pub fn decrypt(&self, ciphertext: [u8; 8]) -> [u8; 8] {
+        self.decrypt_u64(u64::from_be_bytes(ciphertext))
+            .to_be_bytes()
+    }
+
+    pub fn encrypt_u64(&self, plaintext: u64) -> u64 {
+        des(plaintext, self.round_keys.iter().copied())
+    }
+
+    pub fn decrypt_u64(&self, ciphertext: u64) -> u64 {
+        des(ciphertext, self.inv_round_keys.iter().copied())
+    }
+}
+
+impl Zeroize for Des {
+    fn zeroize(&mut self) {
+        self.round_keys.zeroize();
+        self.inv_round_keys.zeroize();
+    }
+}
+
+impl Drop for Des {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl BlockCipher for Des {
+    const BLOCK_SIZE: usize = 8;
+    const KEY_SIZE: usize = 8;
+
+    fn new(key: &[u8]) -> Self {
+        Des::new(key.try_into().expect("invalid key size"))
+    }
+
+    fn encrypt_block(&self, block: &mut [u8]) {
+        let ciphertext = self.encrypt((&*block).try_into().expect("invalid block size"));
+        block.copy_from_slice(&ciphertext);
+    }
+
+    fn decrypt_block(&self, block: &mut [u8]) {
+        let plaintext = self.decrypt((&*block).try_into().expect("invalid block size"));
+        block.copy_from_slice(&plaintext);
+    }
+}
+
+/// Triple DES (TDEA) block cipher in EDE mode
+///
+/// Encryption is `E_K3(D_K2(E_K1(P)))` and decryption is `D_K1(E_K2(D_K3(C)))`. The keying
+/// options from NIST SP 800-67 are:
+///
+/// 1. three independent keys: [`TripleDes::new`],
+/// 2. two independent keys with `K3 = K1`: [`TripleDes::new_2key`],
+/// 3. a single key with `K1 = K2 = K3`, which is equivalent to single DES and only kept for
+///    backward compatibility: `TripleDes::new(k, k, k)`.
+///
+/// https://nvlpubs.nist.gov/nistpubs/SpecialPublications/NIST.SP.800-67r2.pdf
+pub struct TripleDes {
+    k1: Des,
+    k2: Des,
+    k3: Des,
+}
+
+impl TripleDes {
+    pub fn new(k1: [u8; 8], k2: [u8; 8], k3: [u8; 8]) -> Self {
+        Self {
+            k1: Des::new(k1),
+            k2: Des::new(k2),
+            k3: Des::new(k3),
+        }
+    }
+
+    pub fn new_2key(k1: [u8; 8], k2: [u8; 8]) -> Self {
+        Self::new(k1, k2, k1)
+    }
+
+    /// Creates the cipher, rejecting keys with a weak or semi-weak component
+    pub fn new_strict(k1: [u8; 8], k2: [u8; 8], k3: [u8; 8]) -> Result<Self, WeakKey> {
+        for &key in &[k1, k2, k3] {
+            check_strict(key)?;
+        }
+        Ok(Self::new(k1, k2, k3))
+    }
+
+    pub fn encrypt(&self, plaintext: [u8; 8]) -> [u8; 8] {
+        self.encrypt_u64(u64::from_be_bytes(plaintext))
+            .to_be_bytes()
+    }
 
-pub fn encrypt(plaintext: u64, key: u64) -> u64 {
-    des(plaintext, round_keys(key))
+    pub fn decrypt(&self, ciphertext: [u8; 8]) -> [u8; 8] {
+        self.decrypt_u64(u64::from_be_bytes(ciphertext))
+            .to_be_bytes()
+    }
+
+    pub fn encrypt_u64(&self, plaintext: u64) -> u64 {
+        let block = self.k1.encrypt_u64(plaintext);
+        let block = self.k2.decrypt_u64(block);
+        self.k3.encrypt_u64(block)
+    }
+
+    pub fn decrypt_u64(&self, ciphertext: u64) -> u64 {
+        let block = self.k3.decrypt_u64(ciphertext);
+        let block = self.k2.encrypt_u64(block);
+        self.k1.decrypt_u64(block)
+    }
 }
 
-pub fn decrypt(ciphertext: u64, key: u64) -> u64 {
-    let round_keys: Vec<u64> = round_keys(key).collect();
-    des(ciphertext, round_keys.into_iter().rev())
+impl Zeroize for TripleDes {
+    fn zeroize(&mut self) {
+        self.k1.zeroize();
+        self.k2.zeroize();
+        self.k3.zeroize();
+    }
+}
+
+/// Accepts a 24 bytes key `K1 || K2 || K3` or a 16 bytes key `K1 || K2` (keying option 2).
+impl BlockCipher for TripleDes {
+    const BLOCK_SIZE: usize = 8;
+    const KEY_SIZE: usize = 24;
+
+    fn new(key: &[u8]) -> Self {
+        assert!(key.len() == 16 || key.len() == 24, "invalid key size");
+        let k1 = key[0..8].try_into().unwrap();
+        let k2 = key[8..16].try_into().unwrap();
+        match key.get(16..24) {
+            Some(k3) => TripleDes::new(k1, k2, k3.try_into().unwrap()),
+            None => TripleDes::new_2key(k1, k2),
+        }
+    }
+
+    fn encrypt_block(&self, block: &mut [u8]) {
+        let ciphertext = self.encrypt((&*block).try_into().expect("invalid block size"));
+        block.copy_from_slice(&ciphertext);
+    }
+
+    fn decrypt_block(&self, block: &mut [u8]) {
+        let plaintext = self.decrypt((&*block).try_into().expect("invalid block size"));
+        block.copy_from_slice(&plaintext);
+    }
+}
+
+/// Sets the parity bits of `key`, such that each byte has odd parity
+pub fn set_odd_parity(mut key: [u8; 8]) -> Secret<[u8; 8]> {
+    for b in &mut key {
+        let data_bits = *b & 0xfe;
+        *b = data_bits | (data_bits.count_ones() as u8 + 1) & 1;
+    }
+    Secret::new(key)
+}
+
+/// Returns true if each byte of `key` has odd parity
+pub fn has_odd_parity(key: [u8; 8]) -> bool {
+    key.iter().all(|b| b.count_ones() % 2 == 1)
+}
+
+/// Class of a key which generates only few distinct round keys
+///
+/// The parity bits of a key are ignored when it is classified. Only weak and semi-weak keys are
+/// rejected by the strict constructors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WeakKey {
+    /// One of the 4 weak keys, for which encryption and decryption are the same
+    Weak,
+    /// One of the 12 semi-weak keys, which come in pairs encrypting each other's ciphertext
+    SemiWeak,
+    /// A possibly weak key, which generates only 4 distinct round keys
+    ///
+    /// Besides the 48 keys usually tabulated as possibly weak, this includes all other 192 keys
+    /// with this property.
+    PossiblyWeak,
+}
+
+impl fmt::Display for WeakKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WeakKey::Weak => f.write_str("weak DES key"),
+            WeakKey::SemiWeak => f.write_str("semi-weak DES key"),
+            WeakKey::PossiblyWeak => f.write_str("possibly weak DES key"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for WeakKey {}
+
+/// Classifies `key` as weak, semi-weak or possibly weak
+///
+/// A key is degenerate, if the halves C0 and D0 of the key schedule are periodic: all round keys
+/// are rotations of them, so they repeat. With period 1 (only zeros or ones) the key is weak,
+/// with period 2 semi-weak and with period 4 possibly weak.
+pub fn weak_key_class(key: [u8; 8]) -> Option<WeakKey> {
+    let key = permute(&PC1_BITS, 64, u64::from_be_bytes(key));
+    let period = period_28(key >> 28).max(period_28(key & 0xfffffff));
+    match period {
+        1 => Some(WeakKey::Weak),
+        2 => Some(WeakKey::SemiWeak),
+        4 => Some(WeakKey::PossiblyWeak),
+        _ => None,
+    }
+}
+
+/// Rejects weak and semi-weak keys, but not possibly weak ones
+fn check_strict(key: [u8; 8]) -> Result<(), WeakKey> {
+    match weak_key_class(key) {
+        Some(class @ WeakKey::Weak) | Some(class @ WeakKey::SemiWeak) => Err(class),
+        Some(WeakKey::PossiblyWeak) | None => Ok(()),
+    }
+}
+
+pub fn is_weak_key(key: [u8; 8]) -> bool {
+    weak_key_class(key) == Some(WeakKey::Weak)
+}
+
+pub fn is_semi_weak_key(key: [u8; 8]) -> bool {
+    weak_key_class(key) == Some(WeakKey::SemiWeak)
+}
+
+pub fn is_possibly_weak_key(key: [u8; 8]) -> bool {
+    weak_key_class(key) == Some(WeakKey::PossiblyWeak)
+}
+
+/// Smallest period 1, 2 or 4 of the 28 bits, or 28 if they are not periodic
+fn period_28(bits: u64) -> usize {
+    [1, 2, 4]
+        .iter()
+        .copied()
+        .find(|&n| rotate_key_left(bits, n) == bits)
+        .unwrap_or(28)
 }
 
 pub fn des(plaintext: u64, round_keys: impl Iterator<Item = u64>) -> u64 {
-    let preoutput = permute(&IP_BITS, plaintext);
+    let preoutput = permute(&IP_BITS, 64, plaintext);
 
     // Note: the output is swapped
     let (right, left) = round_keys.fold(
-        (preoutput as u32, (preoutput >> 32) as u32),
+        ((preoutput >> 32) as u32, preoutput as u32),
         |(left, right), round_key| (right, left ^ feistel(right, round_key)),
     );
 
-    let preoutput = (right as u64) << 32 | left as u64;
-    permute(&IP_INV_BITS, preoutput)
+    let preoutput = (left as u64) << 32 | right as u64;
+    permute(&IP_INV_BITS, 64, preoutput)
+}
+
+fn round_keys(key: u64) -> RoundKeys {
+    let key = permute(&PC1_BITS, 64, key); // 56 bits
+    RoundKeys {
+        round: 0,
+        left_key: key >> 28,
+        right_key: key & 0xfffffff, // 28 lower bits
+    }
+}
+
+/// Iterator over the 16 round keys, wipes the key halves on drop
+struct RoundKeys {
+    round: usize,
+    left_key: u64,
+    right_key: u64,
 }
 
-fn round_keys(key: u64) -> impl Iterator<Item = u64> {
-    let key = permute(&PC1_BITS, key);
-    let mut left_key = key & 0xfffffff; // 28 lower bits
-    let mut right_key = key >> 28;
+impl Iterator for RoundKeys {
+    type Item = u64;
 
-    (0..16).map(move |round| {
-        left_key = rotate_key_left(left_key, LEFT_SHIFTS[round]);
-        right_key = rotate_key_left(right_key, LEFT_SHIFTS[round]);
-        permute(&PC2_BITS, (right_key << 28) | left_key) // 48 bits
-    })
+    fn next(&mut self) -> Option<Self::Item> {
+        let shift = *LEFT_SHIFTS.get(self.round)?;
+        self.round += 1;
+        self.left_key = rotate_key_left(self.left_key, shift);
+        self.right_key = rotate_key_left(self.right_key, shift);
+        Some(permute(
+            &PC2_BITS,
+            56,
+            (self.left_key << 28) | self.right_key,
+        )) // 48 bits
+    }
+}
+
+impl Drop for RoundKeys {
+    fn drop(&mut self) {
+        self.left_key.zeroize();
+        self.right_key.zeroize();
+    }
 }
 
 fn feistel(block: u32, round_key: u64) -> u32 {
-    let mut mixed = expand(block) ^ round_key;
+    let mixed = expand(block) ^ round_key;
     let mut res = 0;
     for i in 0..8 {
-        let block = mixed as u8 & 0b111111; // 6 bits block
+        let block = (mixed >> (42 - 6 * i)) as u8 & 0b111111; // 6 bits block
         res = res << 4 | (s(block, i) as u32); // 4 bits block output
-        mixed >>= 6;
     }
-    permute(&P_BITS, res as u64) as u32
+    permute(&P_BITS, 32, res as u64) as u32
 }
 
 fn expand(block: u32) -> u64 {
-    permute(&E_BITS, block as u64)
+    permute(&E_BITS, 32, block as u64)
 }
 
-fn permute(perm: &[usize], block: u64) -> u64 {
+/// Permutes the lower `len` bits of `block`
+///
+/// Bits are numbered as in FIPS 46-3, i.e. bit 1 is the most significant one.
+fn permute(perm: &[usize], len: usize, block: u64) -> u64 {
     let mut res = 0;
-    for bit in perm.iter().rev().map(|&b| b - 1) {
-        res <<= 1;
-        if (block >> bit) & 1 == 1 {
-            res |= 1;
-        }
+    for &bit in perm {
+        res = res << 1 | (block >> (len - bit)) & 1;
     }
     res
 }
 
 fn s(block: u8, n: usize) -> u8 {
-    let i = block >> 4 | (block & 1); // 6th and 1st bits
+    let i = (block >> 4) & 0b10 | (block & 1); // 1st and 6th bits
     let j = (block >> 1) & 0b1111; // 2-5 bits
     let idx = i as usize * 16 + j as usize;
     S_BOXES[n][idx]
@@ -166,16 +481,286 @@ mod tests {
     use super::*;
     use crate::hex;
 
+    use quickcheck_macros::quickcheck;
+
     #[test]
     fn test_encrypt() {
-        let plaintext: u64 = u64::from_le_bytes(hex("0000000000C0FFEE").unwrap());
-        let key: u64 = u64::from_le_bytes(hex("000000000000F00D").unwrap());
-        let ciphertext: u64 = u64::from_le_bytes(hex("a271e9bac8862997").unwrap());
+        let plaintext = hex("0000000000C0FFEE").unwrap();
+        let key = hex("000000000000F00D").unwrap();
+        let ciphertext = hex("297a50fb01787bc2").unwrap();
 
         assert_eq!(encrypt(plaintext, key), ciphertext);
         assert_eq!(decrypt(ciphertext, key), plaintext);
     }
 
+    #[test]
+    fn test_encrypt_fips_example() {
+        let plaintext = hex("0123456789ABCDEF").unwrap();
+        let key = hex("133457799BBCDFF1").unwrap();
+        let ciphertext = hex("85E813540F0AB405").unwrap();
+
+        assert_eq!(encrypt(plaintext, key), ciphertext);
+        assert_eq!(decrypt(ciphertext, key), plaintext);
+    }
+
+    #[test]
+    fn test_encrypt_u64() {
+        let plaintext: u64 = 0x0123456789ABCDEF;
+        let key: u64 = 0x133457799BBCDFF1;
+        let ciphertext: u64 = 0x85E813540F0AB405;
+
+        assert_eq!(encrypt_u64(plaintext, key), ciphertext);
+        assert_eq!(decrypt_u64(ciphertext, key), plaintext);
+    }
+
+    #[quickcheck]
+    fn test_bytes_match_u64(plaintext: u64, key: u64) {
+        assert_eq!(
+            encrypt(plaintext.to_be_bytes(), key.to_be_bytes()),
+            encrypt_u64(plaintext, key).to_be_bytes()
+        );
+    }
+
+    #[test]
+    fn test_keyed_multiple_blocks() {
+        let cipher = Des::new(hex("000000000000F00D").unwrap());
+        let plaintext = hex("0000000000C0FFEE").unwrap();
+        let ciphertext = hex("297a50fb01787bc2").unwrap();
+        for _ in 0..3 {
+            assert_eq!(cipher.encrypt(plaintext), ciphertext);
+            assert_eq!(cipher.decrypt(ciphertext), plaintext);
+        }
+    }
+
+    #[test]
+    fn test_block_cipher() {
+        let cipher = <Des as BlockCipher>::new(&hex::<8>("000000000000F00D").unwrap());
+
+        let mut block: [u8; 8] = hex("0000000000C0FFEE").unwrap();
+        cipher.encrypt_block(&mut block);
+        assert_eq!(block, hex("297a50fb01787bc2").unwrap());
+
+        cipher.decrypt_block(&mut block);
+        assert_eq!(block, hex("0000000000C0FFEE").unwrap());
+    }
+
+    #[test]
+    fn test_triple_des_3key() {
+        // SP 800-67 Rev. 2, Appendix B
+        let cipher = TripleDes::new(
+            hex("0123456789ABCDEF").unwrap(),
+            hex("23456789ABCDEF01").unwrap(),
+            hex("456789ABCDEF0123").unwrap(),
+        );
+        let plaintext = b"The qufck brown fox jump";
+        let ciphertext: [u8; 24] =
+            hex("A826FD8CE53B855F CCE21C8112256FE6 68D5C05DD9B6B900").unwrap();
+
+        for (p, c) in plaintext.chunks(8).zip(ciphertext.chunks(8)) {
+            let p = p.try_into().unwrap();
+            let c = c.try_into().unwrap();
+            assert_eq!(cipher.encrypt(p), c);
+            assert_eq!(cipher.decrypt(c), p);
+        }
+    }
+
+    #[test]
+    fn test_triple_des_2key() {
+        let cipher = TripleDes::new_2key(
+            hex("0123456789ABCDEF").unwrap(),
+            hex("23456789ABCDEF01").unwrap(),
+        );
+        let plaintext = b"The qufck brown fox jump";
+        let ciphertext: [u8; 24] =
+            hex("C44862F70CF2FBDC 9077D0909FA91B88 4CABD61FC58E0CBB").unwrap();
+
+        for (p, c) in plaintext.chunks(8).zip(ciphertext.chunks(8)) {
+            let p = p.try_into().unwrap();
+            let c = c.try_into().unwrap();
+            assert_eq!(cipher.encrypt(p), c);
+            assert_eq!(cipher.decrypt(c), p);
+        }
+    }
+
+    #[test]
+    fn test_triple_des_1key() {
+        // CAVP TECBvartext.rsp, COUNT = 0
+        let key = hex("0101010101010101").unwrap();
+        let cipher = TripleDes::new(key, key, key);
+        let plaintext = hex("8000000000000000").unwrap();
+        let ciphertext = hex("95F8A5E5DD31D900").unwrap();
+        assert_eq!(cipher.encrypt(plaintext), ciphertext);
+        assert_eq!(cipher.decrypt(ciphertext), plaintext);
+        assert_eq!(cipher.encrypt_u64(0x8000000000000000), 0x95F8A5E5DD31D900);
+    }
+
+    #[test]
+    fn test_triple_des_block_cipher() {
+        let key: [u8; 16] = hex("0123456789ABCDEF 23456789ABCDEF01").unwrap();
+        let cipher = <TripleDes as BlockCipher>::new(&key);
+        let mut block = *b"The qufc";
+        cipher.encrypt_block(&mut block);
+        assert_eq!(block, hex("C44862F70CF2FBDC").unwrap());
+        cipher.decrypt_block(&mut block);
+        assert_eq!(&block, b"The qufc");
+    }
+
+    #[quickcheck]
+    fn test_triple_des_random(plaintext: u64, k1: u64, k2: u64, k3: u64) {
+        let cipher = TripleDes::new(k1.to_be_bytes(), k2.to_be_bytes(), k3.to_be_bytes());
+        assert_eq!(cipher.decrypt_u64(cipher.encrypt_u64(plaintext)), plaintext);
+    }
+
+    const WEAK_KEYS: [u64; 4] = [
+        0x0101010101010101,
+        0xFEFEFEFEFEFEFEFE,
+        0xE0E0E0E0F1F1F1F1,
+        0x1F1F1F1F0E0E0E0E,
+    ];
+
+    const SEMI_WEAK_KEYS: [u64; 12] = [
+        0x011F011F010E010E,
+        0x1F011F010E010E01,
+        0x01E001E001F101F1,
+        0xE001E001F101F101,
+        0x01FE01FE01FE01FE,
+        0xFE01FE01FE01FE01,
+        0x1FE01FE00EF10EF1,
+        0xE01FE01FF10EF10E,
+        0x1FFE1FFE0EFE0EFE,
+        0xFE1FFE1FFE0EFE0E,
+        0xE0FEE0FEF1FEF1FE,
+        0xFEE0FEE0FEF1FEF1,
+    ];
+
+    #[test]
+    fn test_parity() {
+        let key: [u8; 8] = hex("133457799BBCDFF1").unwrap();
+        assert_eq!(set_odd_parity([0; 8]), [1; 8]);
+        assert_eq!(set_odd_parity(key), key);
+        assert_eq!(set_odd_parity(hex("123456789ABCDEF0").unwrap()), key);
+        assert!(has_odd_parity(key));
+        assert!(!has_odd_parity(hex("123456789ABCDEF0").unwrap()));
+    }
+
+    #[quickcheck]
+    fn test_set_odd_parity_random(key: u64) {
+        let with_parity = set_odd_parity(key.to_be_bytes());
+        assert!(has_odd_parity(*with_parity.expose()));
+        let with_parity = u64::from_be_bytes(*with_parity.expose());
+        assert_eq!(with_parity & !0x0101010101010101, key & !0x0101010101010101);
+        assert_eq!(encrypt_u64(0, with_parity), encrypt_u64(0, key));
+    }
+
+    #[test]
+    fn test_weak_keys() {
+        for &key in &WEAK_KEYS {
+            assert!(is_weak_key(key.to_be_bytes()), "{:016X}", key);
+            assert!(
+                is_weak_key((key ^ 0x0101010101010101).to_be_bytes()),
+                "{:016X}",
+                key
+            );
+            // encryption is an involution
+            assert_eq!(
+                encrypt_u64(encrypt_u64(0x0123456789ABCDEF, key), key),
+                0x0123456789ABCDEF
+            );
+            assert_eq!(
+                Des::new_strict(key.to_be_bytes()).err(),
+                Some(WeakKey::Weak)
+            );
+        }
+    }
+
+    #[test]
+    fn test_semi_weak_keys() {
+        for pair in SEMI_WEAK_KEYS.chunks(2) {
+            for &key in pair {
+                assert!(is_semi_weak_key(key.to_be_bytes()), "{:016X}", key);
+                assert_eq!(
+                    Des::new_strict(key.to_be_bytes()).err(),
+                    Some(WeakKey::SemiWeak)
+                );
+            }
+            // keys of a pair decrypt each other's ciphertexts
+            assert_eq!(
+                encrypt_u64(encrypt_u64(0x0123456789ABCDEF, pair[0]), pair[1]),
+                0x0123456789ABCDEF
+            );
+        }
+    }
+
+    #[test]
+    fn test_possibly_weak_keys() {
+        // enumerate all keys with periodic key schedule halves C0 and D0
+        let nibble_repeated = |n: u64| (0..7).fold(0, |acc, _| acc << 4 | n);
+        let mut counts = [0; 4];
+        for c in 0..16 {
+            for d in 0..16 {
+                let cd = nibble_repeated(c) << 28 | nibble_repeated(d);
+                // invert permuted choice 1
+                let mut key = 0;
+                for (j, &bit) in PC1_BITS.iter().enumerate() {
+                    key |= (cd >> (55 - j) & 1) << (64 - bit);
+                }
+                let key = *set_odd_parity(key.to_be_bytes()).expose();
+                match weak_key_class(key) {
+                    Some(WeakKey::Weak) => counts[0] += 1,
+                    Some(WeakKey::SemiWeak) => counts[1] += 1,
+                    Some(WeakKey::PossiblyWeak) => {
+                        counts[2] += 1;
+                        // the tabulated possibly weak keys have halves built from 0011 rotations
+                        let tabulated = |n| [0x0, 0xF, 0x5, 0xA, 0x3, 0x6, 0xC, 0x9].contains(&n);
+                        if tabulated(c) && tabulated(d) {
+                            counts[3] += 1;
+                        }
+                        let distinct =
+                            round_keys(u64::from_be_bytes(key)).fold(Vec::new(), |mut acc, k| {
+                                if !acc.contains(&k) {
+                                    acc.push(k);
+                                }
+                                acc
+                            });
+                        assert_eq!(distinct.len(), 4, "{:02X?}", key);
+                    }
+                    None => panic!("not classified: {:02X?}", key),
+                }
+            }
+        }
+        assert_eq!(counts, [4, 12, 240, 48]);
+
+        assert!(is_possibly_weak_key(hex("1F1F01010E0E0101").unwrap()));
+        assert!(Des::new_strict(hex("1F1F01010E0E0101").unwrap()).is_ok());
+        assert!(is_possibly_weak_key(hex("E00101E0F10101F1").unwrap()));
+        assert!(!is_possibly_weak_key(hex("133457799BBCDFF1").unwrap()));
+        assert_eq!(weak_key_class(hex("133457799BBCDFF1").unwrap()), None);
+    }
+
+    #[test]
+    fn test_triple_des_strict() {
+        let k1 = hex("0123456789ABCDEF").unwrap();
+        let k2 = hex("23456789ABCDEF01").unwrap();
+        assert!(TripleDes::new_strict(k1, k2, k1).is_ok());
+        assert_eq!(
+            TripleDes::new_strict(k1, SEMI_WEAK_KEYS[0].to_be_bytes(), k1).err(),
+            Some(WeakKey::SemiWeak)
+        );
+        assert_eq!(
+            TripleDes::new_strict(k1, k2, WEAK_KEYS[1].to_be_bytes()).err(),
+            Some(WeakKey::Weak)
+        );
+        assert!(TripleDes::new_strict(k1, hex("E00101E0F10101F1").unwrap(), k1).is_ok());
+    }
+
+    #[test]
+    fn test_zeroize() {
+        let mut cipher = TripleDes::new([0xff; 8], [0xff; 8], [0xff; 8]);
+        cipher.zeroize();
+        assert_eq!(cipher.k2.round_keys, [0; 16]);
+        assert_eq!(cipher.k2.inv_round_keys, [0; 16]);
+    }
+
     #[test]
     fn test_rotate_key_left() {
         assert_eq!(rotate_key_left(1, 1), 2);
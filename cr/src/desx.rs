@@ -0,0 +1,247 @@
+//! DESX block cipher
+//!
+//! DES with pre- and post-whitening: `C = K2 ^ DES_K(P ^ K1)`, where `K` is the 56-bit DES key
+//! and `K1` and `K2` are 64-bit whitening keys, i.e. 184 bits of key material in total.
+//!
+//! https://web.cs.ucdavis.edu/~rogaway/papers/desx.pdf
+//!
+//! Blocks and keys are byte arrays in FIPS 46-3 order as in [`crate::des`].
+//!
+//! The post-whitening key `K2` is either given explicitly, as with the 24 bytes keys of RSAREF
+//! and OpenSSL, or derived from `K` and `K1` with the one-way function of RSA's original DESX,
+//! see [`DesX::new_rsa`].
+
+use crate::des::Des;
+use crate::zeroize::Zeroize;
+use crate::BlockCipher;
+
+use core::convert::TryInto;
+
+/// DESX block cipher
+///
+/// The DES round keys and the whitening keys are wiped on drop.
+pub struct DesX {
+    des: Des,
+    pre_whitening: u64,
+    post_whitening: u64,
+}
+
+impl DesX {
+    /// Creates the cipher from the DES key `k`, pre-whitening key `k1` and post-whitening key `k2`
+    pub fn new(k: [u8; 8], k1: [u8; 8], k2: [u8; 8]) -> Self {
+        Self {
+            des: Des::new(k),
+            pre_whitening: u64::from_be_bytes(k1),
+            post_whitening: u64::from_be_bytes(k2),
+        }
+    }
+
+    /// Creates the cipher from the DES key `k` and pre-whitening key `k1` as RSA's DESX does
+    ///
+    /// The post-whitening key is derived from `k` and `k1` with a one-way function based on the
+    /// substitution table of MD2 (RFC 1319).
+    pub fn new_rsa(k: [u8; 8], k1: [u8; 8]) -> Self {
+        let mut k2 = rsa_post_whitening(k, k1);
+        let cipher = Self::new(k, k1, k2);
+        k2.zeroize();
+        cipher
+    }
+
+    pub fn encrypt(&self, plaintext: [u8; 8]) -> [u8; 8] {
+        self.encrypt_u64(u64::from_be_bytes(plaintext))
+            .to_be_bytes()
+    }
+
+    pub fn decrypt(&self, ciphertext: [u8; 8]) -> [u8; 8] {
+        self.decrypt_u64(u64::from_be_bytes(ciphertext))
+            .to_be_bytes()
+    }
+
+    pub fn encrypt_u64(&self, plaintext: u64) -> u64 {
+        self.des.encrypt_u64(plaintext ^ self.pre_whitening) ^ self.post_whitening
+    }
+
+    pub fn decrypt_u64(&self, ciphertext: u64) -> u64 {
+        self.des.decrypt_u64(ciphertext ^ self.post_whitening) ^ self.pre_whitening
+    }
+}
+
+impl Zeroize for DesX {
+    fn zeroize(&mut self) {
+        self.des.zeroize();
+        self.pre_whitening.zeroize();
+        self.post_whitening.zeroize();
+    }
+}
+
+impl Drop for DesX {
+    fn drop(&mut self) {
+        self.pre_whitening.zeroize();
+        self.post_whitening.zeroize();
+    }
+}
+
+/// Derives the post-whitening key of RSA's DESX from the DES key and the pre-whitening key
+fn rsa_post_whitening(k: [u8; 8], k1: [u8; 8]) -> [u8; 8] {
+    let mut k2 = [0; 8];
+    for &b in k.iter().chain(&k1) {
+        let t = PI_SUBST[(k2[0] ^ k2[1]) as usize];
+        k2.copy_within(1.., 0);
+        k2[7] = t ^ b;
+    }
+    k2
+}
+
+/// Permutation of 0..=255 constructed from the digits of pi, from RFC 1319
+const PI_SUBST: [u8; 256] = [
+    41, 46, 67, 201, 162, 216, 124, 1, 61, 54, 84, 161, 236, 240, 6, 19, 98, 167, 5, 243, 192, 199,
+    115, 140, 152, 147, 43, 217, 188, 76, 130, 202, 30, 155, 87, 60, 253, 212, 224, 22, 103, 66,
+    111, 24, 138, 23, 229, 18, 190, 78, 196, 214, 218, 158, 222, 73, 160, 251, 245, 142, 187, 47,
+    238, 122, 169, 104, 121, 145, 21, 178, 7, 63, 148, 194, 16, 137, 11, 34, 95, 33, 128, 127, 93,
+    154, 90, 144, 50, 39, 53, 62, 204, 231, 191, 247, 151, 3, 255, 25, 48, 179, 72, 165, 181, 209,
+    215, 94, 146, 42, 172, 86, 170, 198, 79, 184, 56, 210, 150, 164, 125, 182, 118, 252, 107, 226,
+    156, 116, 4, 241, 69, 157, 112, 89, 100, 113, 135, 32, 134, 91, 207, 101, 230, 45, 168, 2, 27,
+    96, 37, 173, 174, 176, 185, 246, 28, 70, 97, 105, 52, 64, 126, 15, 85, 71, 163, 35, 221, 81,
+    175, 58, 195, 92, 249, 206, 186, 197, 234, 38, 44, 83, 13, 110, 133, 40, 132, 9, 211, 223, 205,
+    244, 65, 129, 77, 82, 106, 220, 55, 200, 108, 193, 171, 250, 36, 225, 123, 8, 12, 189, 177, 74,
+    120, 136, 149, 139, 227, 99, 232, 109, 233, 203, 213, 254, 59, 0, 29, 57, 242, 239, 183, 14,
+    102, 88, 208, 228, 166, 119, 114, 248, 235, 117, 75, 10, 49, 68, 80, 180, 143, 237, 31, 26,
+    219, 153, 141, 51, 159, 17, 131, 20,
+];
+
+/// Expects a 24 bytes key `K || K1 || K2`, which is the layout used by RSAREF and OpenSSL.
+impl BlockCipher for DesX {
+    const BLOCK_SIZE: usize = 8;
+    const KEY_SIZE: usize = 24;
+
+    fn new(key: &[u8]) -> Self {
+        assert_eq!(key.len(), Self::KEY_SIZE, "invalid key size");
+        let k = key[0..8].try_into().unwrap();
+        let k1 = key[8..16].try_into().unwrap();
+        let k2 = key[16..24].try_into().unwrap();
+        DesX::new(k, k1, k2)
+    }
+
+    fn encrypt_block(&self, block: &mut [u8]) {
+        let ciphertext = self.encrypt((&*block).try_into().expect("invalid block size"));
+        block.copy_from_slice(&ciphertext);
+    }
+
+    fn decrypt_block(&self, block: &mut [u8]) {
+        let plaintext = self.decrypt((&*block).try_into().expect("invalid block size"));
+        block.copy_from_slice(&plaintext);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex;
+
+    use quickcheck_macros::quickcheck;
+
+    #[test]
+    fn test_encrypt() {
+        let cipher = DesX::new(
+            hex("0123456789ABCDEF").unwrap(),
+            hex("1011121314151617").unwrap(),
+            hex("F0E0D0C0B0A09080").unwrap(),
+        );
+        assert_eq!(
+            cipher.encrypt(*b"Now is t"),
+            hex("4ACF28C91CE3228D").unwrap()
+        );
+        assert_eq!(
+            cipher.decrypt(hex("4ACF28C91CE3228D").unwrap()),
+            *b"Now is t"
+        );
+
+        let cipher = DesX::new(
+            hex("0101010101010101").unwrap(),
+            hex("0123456789ABCDEF").unwrap(),
+            hex("FEDCBA9876543210").unwrap(),
+        );
+        assert_eq!(cipher.encrypt_u64(0x94DBE082549A14EF), 0x7EDCBA9876543210);
+        assert_eq!(cipher.decrypt_u64(0x7EDCBA9876543210), 0x94DBE082549A14EF);
+    }
+
+    #[test]
+    fn test_new_rsa() {
+        // computed with a separate implementation of the derivation and OpenSSL's DES
+        let vectors = [
+            (
+                "0123456789ABCDEF",
+                "1011121314151617",
+                "47A4455843A0BA5D",
+                "4E6F772069732074",
+                "FD8BBD51EFE30850",
+            ),
+            (
+                "0101010101010101",
+                "0000000000000000",
+                "2929292929295F21",
+                "0000000000000000",
+                "A58F64C0E8987C86",
+            ),
+            (
+                "133457799BBCDFF1",
+                "FEDCBA9876543210",
+                "E86F5F2F60E762FB",
+                "0123456789ABCDEF",
+                "B252EC2BB6AE4606",
+            ),
+        ];
+        for &(k, k1, k2, plaintext, ciphertext) in &vectors {
+            let (k, k1) = (hex(k).unwrap(), hex(k1).unwrap());
+            assert_eq!(rsa_post_whitening(k, k1), hex(k2).unwrap());
+
+            let cipher = DesX::new_rsa(k, k1);
+            assert_eq!(
+                cipher.encrypt(hex(plaintext).unwrap()),
+                hex(ciphertext).unwrap()
+            );
+            assert_eq!(
+                cipher.decrypt(hex(ciphertext).unwrap()),
+                hex(plaintext).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_without_whitening_is_des() {
+        let cipher = DesX::new(hex("133457799BBCDFF1").unwrap(), [0; 8], [0; 8]);
+        assert_eq!(
+            cipher.encrypt(hex("0123456789ABCDEF").unwrap()),
+            hex("85E813540F0AB405").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_block_cipher() {
+        let key: [u8; 24] = hex("0123456789ABCDEF 1011121314151617 F0E0D0C0B0A09080").unwrap();
+        let cipher = <DesX as BlockCipher>::new(&key);
+
+        let mut block: [u8; 8] = hex("0123456789ABCDEF").unwrap();
+        cipher.encrypt_block(&mut block);
+        assert_eq!(block, hex("AACA5357F8CE9D4F").unwrap());
+
+        cipher.decrypt_block(&mut block);
+        assert_eq!(block, hex("0123456789ABCDEF").unwrap());
+    }
+
+    #[test]
+    fn test_zeroize() {
+        let mut cipher = DesX::new([0xff; 8], [0xff; 8], [0xff; 8]);
+        cipher.zeroize();
+        assert_eq!(cipher.pre_whitening, 0);
+        assert_eq!(cipher.post_whitening, 0);
+        // the round keys of the all-zero DES key are zero as well
+        assert_eq!(cipher.encrypt([0; 8]), crate::des::encrypt([0; 8], [0; 8]));
+    }
+
+    #[quickcheck]
+    fn test_encrypt_decrypt_random(plaintext: u64, k: u64, k1: u64, k2: u64) {
+        let cipher = DesX::new(k.to_be_bytes(), k1.to_be_bytes(), k2.to_be_bytes());
+        assert_eq!(cipher.decrypt_u64(cipher.encrypt_u64(plaintext)), plaintext);
+    }
+}
@@ -0,0 +1,544 @@
+//! Hex, base32 and base64 encodings
+//!
+//! https://datatracker.ietf.org/doc/html/rfc4648
+//!
+//! An [`Encoding`] maps groups of 4 (hex), 5 (base32) or 6 (base64) bits to ASCII symbols. Data
+//! is encoded and decoded either into caller provided buffers, which does not allocate, or in
+//! chunks with [`Encoder`] and [`Decoder`]. With the `std` feature, [`Encoding::encode`] and
+//! [`Encoding::decode`] return a `String` and `Vec` respectively.
+//!
+//! Decoding ignores whitespace (space, tab, CR and LF), accepts hex symbols in both cases, and
+//! rejects input with missing or superfluous padding or with non-zero trailing bits.
+
+use core::fmt;
+
+/// Error for invalid input and too small output buffers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncodingError {
+    /// The input contains a byte which is not a symbol of the alphabet
+    InvalidSymbol(u8),
+    /// The number of symbols does not correspond to a whole number of bytes
+    InvalidLength,
+    /// Padding is missing, superfluous, or followed by symbols
+    InvalidPadding,
+    /// The unused bits of the last symbol are not zero
+    TrailingBits,
+    /// The output buffer is too small
+    BufferTooSmall,
+}
+
+impl fmt::Display for EncodingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EncodingError::InvalidSymbol(b) => write!(f, "invalid symbol: 0x{:02x}", b),
+            EncodingError::InvalidLength => f.write_str("invalid length"),
+            EncodingError::InvalidPadding => f.write_str("invalid padding"),
+            EncodingError::TrailingBits => f.write_str("non-zero trailing bits"),
+            EncodingError::BufferTooSmall => f.write_str("output buffer too small"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for EncodingError {}
+
+const INVALID: u8 = 0xff;
+const PAD: u8 = b'=';
+
+/// Binary-to-text encoding with an alphabet of 16, 32 or 64 symbols
+#[derive(Debug, Clone, Copy)]
+pub struct Encoding {
+    symbols: &'static [u8],
+    values: &'static [u8; 256],
+    /// Number of bits per symbol
+    bits: u32,
+    padding: bool,
+}
+
+/// Hex with lowercase symbols
+pub const HEX: Encoding = Encoding::new(b"0123456789abcdef", &HEX_VALUES, false);
+/// Hex with uppercase symbols
+pub const HEX_UPPER: Encoding = Encoding::new(b"0123456789ABCDEF", &HEX_VALUES, false);
+/// Base32 with padding
+pub const BASE32: Encoding = Encoding::new(BASE32_SYMBOLS, &BASE32_VALUES, true);
+/// Base64 with padding
+pub const BASE64: Encoding = Encoding::new(BASE64_SYMBOLS, &BASE64_VALUES, true);
+/// URL and filename safe base64 with padding
+pub const BASE64URL: Encoding = Encoding::new(BASE64URL_SYMBOLS, &BASE64URL_VALUES, true);
+/// URL and filename safe base64 without padding
+pub const BASE64URL_NOPAD: Encoding = Encoding::new(BASE64URL_SYMBOLS, &BASE64URL_VALUES, false);
+
+const BASE32_SYMBOLS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+const BASE64_SYMBOLS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const BASE64URL_SYMBOLS: &[u8] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+const HEX_VALUES: [u8; 256] = {
+    let mut values = symbol_values(b"0123456789abcdef");
+    let mut i = 10;
+    while i < 16 {
+        values[b"ABCDEF"[i - 10] as usize] = i as u8;
+        i += 1;
+    }
+    values
+};
+const BASE32_VALUES: [u8; 256] = symbol_values(BASE32_SYMBOLS);
+const BASE64_VALUES: [u8; 256] = symbol_values(BASE64_SYMBOLS);
+const BASE64URL_VALUES: [u8; 256] = symbol_values(BASE64URL_SYMBOLS);
+
+/// Inverts the alphabet `symbols`; bytes which are not symbols are mapped to `INVALID`
+const fn symbol_values(symbols: &[u8]) -> [u8; 256] {
+    let mut values = [INVALID; 256];
+    let mut i = 0;
+    while i < symbols.len() {
+        values[symbols[i] as usize] = i as u8;
+        i += 1;
+    }
+    values
+}
+
+fn is_whitespace(b: u8) -> bool {
+    matches!(b, b' ' | b'\t' | b'\r' | b'\n')
+}
+
+impl Encoding {
+    const fn new(symbols: &'static [u8], values: &'static [u8; 256], padding: bool) -> Self {
+        Self {
+            symbols,
+            values,
+            bits: symbols.len().trailing_zeros(),
+            padding,
+        }
+    }
+
+    /// Number of symbols encoding a whole number of bytes, e.g. 4 symbols for 3 bytes in base64
+    fn group_len(&self) -> usize {
+        match self.bits {
+            4 => 2,
+            5 => 8,
+            _ => 4,
+        }
+    }
+
+    /// Length of the encoding of `len` bytes, including padding
+    pub fn encoded_len(&self, len: usize) -> usize {
+        let symbols = (8 * len).div_ceil(self.bits as usize);
+        if self.padding {
+            let group_len = self.group_len();
+            symbols.div_ceil(group_len) * group_len
+        } else {
+            symbols
+        }
+    }
+
+    /// Upper bound of the number of bytes decoded from `len` symbols
+    pub fn decoded_len(&self, len: usize) -> usize {
+        (len * self.bits as usize).div_ceil(8)
+    }
+
+    /// Starts a chunked encoding
+    pub fn encoder(&self) -> Encoder {
+        Encoder {
+            encoding: *self,
+            acc: 0,
+            acc_bits: 0,
+            len: 0,
+        }
+    }
+
+    /// Starts a chunked decoding
+    pub fn decoder(&self) -> Decoder {
+        Decoder {
+            encoding: *self,
+            acc: 0,
+            acc_bits: 0,
+            len: 0,
+            padding: 0,
+        }
+    }
+
+    /// Encodes `input` into `output`, which must hold at least `encoded_len(input.len())` bytes
+    pub fn encode_to_slice<'a>(
+        &self,
+        input: &[u8],
+        output: &'a mut [u8],
+    ) -> Result<&'a str, EncodingError> {
+        let len = self.encoded_len(input.len());
+        let output = output.get_mut(..len).ok_or(EncodingError::BufferTooSmall)?;
+        let mut encoder = self.encoder();
+        let n = encoder.update(input, output)?;
+        encoder.finish(&mut output[n..])?;
+        Ok(core::str::from_utf8(output).expect("alphabet is ASCII"))
+    }
+
+    /// Decodes `input` into `output` and returns the decoded bytes
+    ///
+    /// `output` must hold at least `decoded_len(input.len())` bytes.
+    pub fn decode_to_slice<'a>(
+        &self,
+        input: impl AsRef<[u8]>,
+        output: &'a mut [u8],
+    ) -> Result<&'a [u8], EncodingError> {
+        let mut decoder = self.decoder();
+        let n = decoder.update(input.as_ref(), output)?;
+        decoder.finish()?;
+        Ok(&output[..n])
+    }
+
+    /// Decodes `input`, which must encode exactly `N` bytes
+    pub fn decode_array<const N: usize>(
+        &self,
+        input: impl AsRef<[u8]>,
+    ) -> Result<[u8; N], EncodingError> {
+        let mut ar = [0; N];
+        let mut idx = 0;
+        let mut decoder = self.decoder();
+        for &b in input.as_ref() {
+            if let Some(byte) = decoder.push(b)? {
+                *ar.get_mut(idx).ok_or(EncodingError::InvalidLength)? = byte;
+                idx += 1;
+            }
+        }
+        decoder.finish()?;
+        if idx == N {
+            Ok(ar)
+        } else {
+            Err(EncodingError::InvalidLength)
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub fn encode(&self, input: impl AsRef<[u8]>) -> String {
+        let input = input.as_ref();
+        let mut output = vec![0; self.encoded_len(input.len())];
+        self.encode_to_slice(input, &mut output)
+            .expect("buffer has the encoded length");
+        String::from_utf8(output).expect("alphabet is ASCII")
+    }
+
+    #[cfg(feature = "std")]
+    pub fn decode(&self, input: impl AsRef<[u8]>) -> Result<Vec<u8>, EncodingError> {
+        let input = input.as_ref();
+        let mut output = vec![0; self.decoded_len(input.len())];
+        let len = self.decode_to_slice(input, &mut output)?.len();
+        output.truncate(len);
+        Ok(output)
+    }
+}
+
+/// Chunked encoder
+///
+/// Input can be split at arbitrary positions; the symbols are written as soon as their bits
+/// are complete.
+#[derive(Debug, Clone)]
+pub struct Encoder {
+    encoding: Encoding,
+    /// Bits which are not yet encoded, in the lowest `acc_bits` bits
+    acc: u32,
+    acc_bits: u32,
+    /// Number of symbols written so far
+    len: usize,
+}
+
+impl Encoder {
+    /// Encodes `input` into `output` and returns the number of symbols written
+    ///
+    /// `output` must hold at least `encoded_len(input.len())` bytes.
+    pub fn update(&mut self, input: &[u8], output: &mut [u8]) -> Result<usize, EncodingError> {
+        let bits = self.encoding.bits;
+        let len = (self.acc_bits as usize + 8 * input.len()) / bits as usize;
+        if output.len() < len {
+            return Err(EncodingError::BufferTooSmall);
+        }
+
+        let mask = (1 << bits) - 1;
+        let mut idx = 0;
+        for &b in input {
+            self.acc = self.acc << 8 | b as u32;
+            self.acc_bits += 8;
+            while self.acc_bits >= bits {
+                self.acc_bits -= bits;
+                output[idx] = self.encoding.symbols[(self.acc >> self.acc_bits & mask) as usize];
+                idx += 1;
+            }
+            self.acc &= (1 << self.acc_bits) - 1;
+        }
+        self.len += idx;
+        Ok(idx)
+    }
+
+    /// Writes the last symbol and the padding to `output` and returns the number of bytes written
+    ///
+    /// `output` must hold at least as many bytes as a group of symbols, e.g. 4 in base64.
+    pub fn finish(mut self, output: &mut [u8]) -> Result<usize, EncodingError> {
+        let mut len = self.len + (self.acc_bits > 0) as usize;
+        if self.encoding.padding {
+            let group_len = self.encoding.group_len();
+            len = len.div_ceil(group_len) * group_len;
+        }
+        let output = output
+            .get_mut(..len - self.len)
+            .ok_or(EncodingError::BufferTooSmall)?;
+
+        let mut idx = 0;
+        if self.acc_bits > 0 {
+            let value = self.acc << (self.encoding.bits - self.acc_bits);
+            output[idx] = self.encoding.symbols[value as usize];
+            idx += 1;
+            self.acc = 0;
+            self.acc_bits = 0;
+        }
+        for b in &mut output[idx..] {
+            *b = PAD;
+        }
+        Ok(output.len())
+    }
+}
+
+/// Chunked decoder
+///
+/// Input can be split at arbitrary positions; the bytes are written as soon as their bits are
+/// complete. The input is validated completely only by [`Decoder::finish`].
+#[derive(Debug, Clone)]
+pub struct Decoder {
+    encoding: Encoding,
+    /// Bits which are not yet decoded, in the lowest `acc_bits` bits
+    acc: u32,
+    acc_bits: u32,
+    /// Number of symbols read so far, excluding padding and whitespace
+    len: usize,
+    /// Number of padding symbols read so far
+    padding: usize,
+}
+
+impl Decoder {
+    /// Decodes `input` into `output` and returns the number of bytes written
+    ///
+    /// `output` must hold at least `decoded_len(input.len())` bytes.
+    pub fn update(&mut self, input: &[u8], output: &mut [u8]) -> Result<usize, EncodingError> {
+        let len = (self.acc_bits as usize + self.encoding.bits as usize * input.len()) / 8;
+        if output.len() < len {
+            return Err(EncodingError::BufferTooSmall);
+        }
+
+        let mut idx = 0;
+        for &b in input {
+            if let Some(byte) = self.push(b)? {
+                output[idx] = byte;
+                idx += 1;
+            }
+        }
+        Ok(idx)
+    }
+
+    /// Validates the end of the input
+    pub fn finish(self) -> Result<(), EncodingError> {
+        if self.acc_bits >= self.encoding.bits {
+            return Err(EncodingError::InvalidLength);
+        }
+        if self.acc != 0 {
+            return Err(EncodingError::TrailingBits);
+        }
+        let group_len = self.encoding.group_len();
+        if self.encoding.padding && self.padding != (group_len - self.len % group_len) % group_len {
+            return Err(EncodingError::InvalidPadding);
+        }
+        Ok(())
+    }
+
+    /// Reads a single byte of input and returns the decoded byte once its bits are complete
+    fn push(&mut self, b: u8) -> Result<Option<u8>, EncodingError> {
+        if is_whitespace(b) {
+            return Ok(None);
+        }
+        if b == PAD && self.encoding.padding {
+            self.padding += 1;
+            return Ok(None);
+        }
+        let value = self.encoding.values[b as usize];
+        if value == INVALID {
+            return Err(EncodingError::InvalidSymbol(b));
+        }
+        if self.padding > 0 {
+            return Err(EncodingError::InvalidPadding);
+        }
+
+        self.len += 1;
+        self.acc = self.acc << self.encoding.bits | value as u32;
+        self.acc_bits += self.encoding.bits;
+        if self.acc_bits >= 8 {
+            self.acc_bits -= 8;
+            let byte = (self.acc >> self.acc_bits) as u8;
+            self.acc &= (1 << self.acc_bits) - 1;
+            Ok(Some(byte))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use quickcheck_macros::quickcheck;
+
+    // `Encoding::encode` and `Encoding::decode` need the `std` feature
+    fn encode(encoding: &Encoding, input: impl AsRef<[u8]>) -> String {
+        let input = input.as_ref();
+        let mut output = vec![0; encoding.encoded_len(input.len())];
+        let len = encoding.encode_to_slice(input, &mut output).unwrap().len();
+        output.truncate(len);
+        String::from_utf8(output).unwrap()
+    }
+
+    fn decode(encoding: &Encoding, input: impl AsRef<[u8]>) -> Result<Vec<u8>, EncodingError> {
+        let input = input.as_ref();
+        let mut output = vec![0; encoding.decoded_len(input.len())];
+        let len = encoding.decode_to_slice(input, &mut output)?.len();
+        output.truncate(len);
+        Ok(output)
+    }
+
+    // RFC 4648, section 10
+    const VECTORS: [&str; 7] = ["", "f", "fo", "foo", "foob", "fooba", "foobar"];
+
+    #[test]
+    fn test_base64() {
+        let expected = [
+            "", "Zg==", "Zm8=", "Zm9v", "Zm9vYg==", "Zm9vYmE=", "Zm9vYmFy",
+        ];
+        for (input, expected) in VECTORS.iter().zip(&expected) {
+            assert_eq!(encode(&BASE64, input), *expected);
+            assert_eq!(decode(&BASE64, expected).unwrap(), input.as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_base32() {
+        let expected = [
+            "",
+            "MY======",
+            "MZXQ====",
+            "MZXW6===",
+            "MZXW6YQ=",
+            "MZXW6YTB",
+            "MZXW6YTBOI======",
+        ];
+        for (input, expected) in VECTORS.iter().zip(&expected) {
+            assert_eq!(encode(&BASE32, input), *expected);
+            assert_eq!(decode(&BASE32, expected).unwrap(), input.as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_hex() {
+        assert_eq!(encode(&HEX, b"foobar"), "666f6f626172");
+        assert_eq!(encode(&HEX_UPPER, [0xde, 0xad, 0xbe, 0xef]), "DEADBEEF");
+        assert_eq!(
+            decode(&HEX, "De ad\r\n\tbE Ef").unwrap(),
+            [0xde, 0xad, 0xbe, 0xef]
+        );
+        assert_eq!(HEX.decode_array::<2>("0aFf"), Ok([0x0a, 0xff]));
+        assert_eq!(
+            HEX.decode_array::<2>("0aff00"),
+            Err(EncodingError::InvalidLength)
+        );
+        assert_eq!(
+            HEX.decode_array::<2>("0af"),
+            Err(EncodingError::InvalidLength)
+        );
+        assert_eq!(decode(&HEX, "0g"), Err(EncodingError::InvalidSymbol(b'g')));
+    }
+
+    #[test]
+    fn test_base64url() {
+        let input = [0xfb, 0xff, 0xbf];
+        assert_eq!(encode(&BASE64, input), "+/+/");
+        assert_eq!(encode(&BASE64URL, input), "-_-_");
+        assert_eq!(encode(&BASE64URL, [0xfb]), "-w==");
+        assert_eq!(encode(&BASE64URL_NOPAD, [0xfb]), "-w");
+        assert_eq!(decode(&BASE64URL_NOPAD, "-w").unwrap(), [0xfb]);
+        assert_eq!(
+            decode(&BASE64URL_NOPAD, "-w=="),
+            Err(EncodingError::InvalidSymbol(b'='))
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert_eq!(decode(&BASE64, "Zg="), Err(EncodingError::InvalidPadding));
+        assert_eq!(decode(&BASE64, "Zg"), Err(EncodingError::InvalidPadding));
+        assert_eq!(decode(&BASE64, "Zg==="), Err(EncodingError::InvalidPadding));
+        assert_eq!(
+            decode(&BASE64, "Zg==Zg=="),
+            Err(EncodingError::InvalidPadding)
+        );
+        assert_eq!(decode(&BASE64, "Zh=="), Err(EncodingError::TrailingBits));
+        assert_eq!(decode(&BASE64, "Z==="), Err(EncodingError::InvalidLength));
+        assert_eq!(
+            decode(&BASE32, "MZX====="),
+            Err(EncodingError::InvalidLength)
+        );
+        assert_eq!(
+            decode(&BASE64, "Zm9v!"),
+            Err(EncodingError::InvalidSymbol(b'!'))
+        );
+    }
+
+    #[test]
+    fn test_slices() {
+        let mut buf = [0; 8];
+        assert_eq!(BASE64.encode_to_slice(b"foob", &mut buf), Ok("Zm9vYg=="));
+        assert_eq!(
+            BASE64.encode_to_slice(b"foob", &mut buf[..7]),
+            Err(EncodingError::BufferTooSmall)
+        );
+        assert_eq!(
+            BASE64.decode_to_slice("Zm9vYg==", &mut buf),
+            Ok(&b"foob"[..])
+        );
+        assert_eq!(
+            BASE64.decode_to_slice("Zm9vYg==", &mut buf[..5]),
+            Err(EncodingError::BufferTooSmall)
+        );
+    }
+
+    fn encode_chunked(encoding: &Encoding, input: &[u8], split: usize) -> String {
+        let (head, tail) = input.split_at(split % (input.len() + 1));
+        let mut output = vec![0; encoding.encoded_len(input.len()) + encoding.group_len()];
+        let mut encoder = encoding.encoder();
+        let mut len = encoder.update(head, &mut output).unwrap();
+        len += encoder.update(tail, &mut output[len..]).unwrap();
+        len += encoder.finish(&mut output[len..]).unwrap();
+        output.truncate(len);
+        String::from_utf8(output).unwrap()
+    }
+
+    fn decode_chunked(encoding: &Encoding, input: &str, split: usize) -> Vec<u8> {
+        let (head, tail) = input.as_bytes().split_at(split % (input.len() + 1));
+        let mut output = vec![0; encoding.decoded_len(input.len())];
+        let mut decoder = encoding.decoder();
+        let mut len = decoder.update(head, &mut output).unwrap();
+        len += decoder.update(tail, &mut output[len..]).unwrap();
+        decoder.finish().unwrap();
+        output.truncate(len);
+        output
+    }
+
+    #[quickcheck]
+    fn test_roundtrip(input: Vec<u8>, split: usize) {
+        for encoding in &[HEX, HEX_UPPER, BASE32, BASE64, BASE64URL, BASE64URL_NOPAD] {
+            let encoded = encode(encoding, &input);
+            assert_eq!(encoded.len(), encoding.encoded_len(input.len()));
+            assert_eq!(encode_chunked(encoding, &input, split), encoded);
+            assert_eq!(decode(encoding, &encoded).unwrap(), input);
+            assert_eq!(decode_chunked(encoding, &encoded, split), input);
+            #[cfg(feature = "std")]
+            {
+                assert_eq!(encoding.encode(&input), encoded);
+                assert_eq!(encoding.decode(&encoded).unwrap(), input);
+            }
+        }
+    }
+}
@@ -0,0 +1,82 @@
+//! Hashing of readers and writers
+//!
+//! The hash functions implement [`std::io::Write`], so they can be used as the destination of
+//! [`std::io::copy`] or wrapped by any writer adapter.
+
+use crate::md5::Md5;
+use crate::sha1::Sha1;
+use crate::Hasher;
+
+use std::io::{self, Read, Write};
+
+macro_rules! impl_write {
+    ($name:ident) => {
+        impl Write for $name {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.update(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_write!(Md5);
+impl_write!(Sha1);
+
+/// Hashes all bytes read from `reader` until EOF, e.g. `hash_reader::<Sha1>(file)`
+pub fn hash_reader<H: Hasher + Write>(mut reader: impl Read) -> io::Result<H::Output> {
+    let mut hasher = H::default();
+    io::copy(&mut reader, &mut hasher)?;
+    Ok(hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex;
+
+    #[test]
+    fn test_hash_reader() {
+        let input = vec![b'a'; 1000];
+        assert_eq!(
+            hash_reader::<Md5>(&input[..]).unwrap(),
+            crate::md5::md5(&input)
+        );
+        assert_eq!(
+            hash_reader::<Sha1>(&input[..]).unwrap(),
+            crate::sha1::sha1(&input)
+        );
+    }
+
+    #[test]
+    fn test_write() {
+        let mut hasher = Sha1::new();
+        hasher.write_all(b"abc").unwrap();
+        assert_eq!(
+            hasher.finalize_reset(),
+            hex("a9993e364706816aba3e25717850c26c9cd0d89d").unwrap()
+        );
+        io::copy(&mut &b"abc"[..], &mut hasher).unwrap();
+        assert_eq!(
+            hasher.finalize(),
+            hex("a9993e364706816aba3e25717850c26c9cd0d89d").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut hasher = Md5::new();
+        hasher.update(b"garbage");
+        Hasher::reset(&mut hasher);
+        hasher.update(b"abc");
+        assert_eq!(
+            hasher.clone().finalize(),
+            hex("900150983cd24fb0d6963f7d28e17f72").unwrap()
+        );
+        assert_eq!(hasher.finalize(), crate::md5::md5(b"abc"));
+    }
+}
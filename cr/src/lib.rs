@@ -1,47 +1,94 @@
+//! Implementations of cryptographic primitives
+//!
+//! The crate is `no_std` and does not allocate. The default `std` feature adds the [`io`]
+//! adapters and `std::error::Error` implementations for the error types.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
 pub mod aes;
+pub mod ct;
 pub mod des;
+pub mod desx;
+pub mod encoding;
+#[cfg(feature = "std")]
+pub mod io;
 pub mod md5;
+pub mod midstate;
+pub mod modes;
+#[cfg(feature = "std")]
+pub mod registry;
+#[cfg(any(feature = "digest", feature = "cipher"))]
+mod rustcrypto;
 pub mod sha1;
 pub mod twofish;
+pub mod zeroize;
 
-#[cfg(test)]
-pub fn hex<const N: usize>(s: impl AsRef<str>) -> Option<[u8; N]> {
-    let mut ar = [0; N];
-    let mut idx = 0;
-    for c in s.as_ref().as_bytes().iter() {
-        if idx >= 2 * N {
-            return None;
-        }
-        let b = match c {
-            b'0'..=b'9' => c - 48,
-            b'A'..=b'F' => c - 55,
-            b'a'..=b'f' => c - 87,
-            b' ' | b'\r' | b'\n' | b'\t' => continue,
-            _ => return None,
-        };
-        ar[idx / 2] = ar[idx / 2] << 4 | b;
-        idx += 1;
+/// Block cipher keyed with a fixed size key
+///
+/// Keys and blocks are passed as byte slices, which must have exactly `KEY_SIZE` and
+/// `BLOCK_SIZE` bytes respectively; otherwise the methods panic.
+pub trait BlockCipher {
+    /// Block size in bytes
+    const BLOCK_SIZE: usize;
+    /// Key size in bytes
+    const KEY_SIZE: usize;
+
+    fn new(key: &[u8]) -> Self;
+
+    /// Encrypts a single block in place
+    fn encrypt_block(&self, block: &mut [u8]);
+
+    /// Decrypts a single block in place
+    fn decrypt_block(&self, block: &mut [u8]);
+}
+
+/// Hash function with a fixed size output
+///
+/// Input is absorbed with [`Hasher::update`] in any number of calls; the hash is computed by
+/// [`Hasher::finalize`] or [`Hasher::finalize_reset`].
+pub trait Hasher: Clone + Default {
+    /// Output size in bytes
+    const OUTPUT_SIZE: usize;
+    /// Internal block size in bytes
+    const BLOCK_SIZE: usize;
+
+    /// Hash value, an array of `OUTPUT_SIZE` bytes wrapped in [`ct::Secret`]
+    type Output: AsRef<[u8]> + Clone;
+
+    fn update(&mut self, input: &[u8]) -> &mut Self;
+
+    fn finalize(self) -> Self::Output;
+
+    /// Computes the hash and resets the state for hashing a new message
+    fn finalize_reset(&mut self) -> Self::Output {
+        core::mem::take(self).finalize()
     }
-    if idx == 2 * N {
-        Some(ar)
-    } else {
-        None
+
+    /// Resets the state, discarding all input absorbed so far
+    fn reset(&mut self) {
+        *self = Self::default();
     }
 }
 
+#[cfg(test)]
+pub fn hex<const N: usize>(s: impl AsRef<str>) -> Option<[u8; N]> {
+    encoding::HEX.decode_array(s.as_ref()).ok()
+}
+
+#[cfg(test)]
+pub fn hex_vec(s: impl AsRef<str>) -> Vec<u8> {
+    let s = s.as_ref();
+    let mut buf = vec![0; encoding::HEX.decoded_len(s.len())];
+    let len = encoding::HEX.decode_to_slice(s, &mut buf).unwrap().len();
+    buf.truncate(len);
+    buf
+}
+
 #[cfg(test)]
 pub fn hex_string<const N: usize>(ar: [u8; N]) -> String {
-    let mut s = String::new();
-    for &b in ar.iter() {
-        for i in (0..2).rev() {
-            let v = (b >> (4 * i)) & 0xF;
-            let c = match v {
-                0..=9 => v + 48,
-                10..=16 => v + 55,
-                _ => unreachable!(),
-            };
-            s.push(c as char);
-        }
-    }
-    s
+    let mut buf = vec![0; 2 * N];
+    encoding::HEX_UPPER
+        .encode_to_slice(&ar, &mut buf)
+        .unwrap()
+        .to_string()
 }
@@ -3,12 +3,64 @@
 //! https://datatracker.ietf.org/doc/html/rfc1321
 #![allow(clippy::many_single_char_names)]
 
-pub fn md5(input: &[u8]) -> [u8; 16] {
+use crate::ct::Secret;
+use crate::midstate::{self, MidstateError};
+use crate::zeroize::Zeroize;
+use crate::Hasher;
+
+use core::convert::TryInto;
+
+/// Size of an exported midstate in bytes, see [`crate::midstate`]
+pub const MIDSTATE_SIZE: usize = 90;
+
+/// Computes the MD5 hash of `input`
+pub fn md5(input: &[u8]) -> Secret<[u8; 16]> {
     let mut state = Md5::new();
     state.update(input);
     state.digest()
 }
 
+/// Computes the MD5 hash of `input` in a const context
+///
+/// This can be used to compute hashes at compile time, e.g. of embedded data. It is slower than
+/// [`md5`] and does not wipe its intermediate state, so it must not be used for secret input.
+pub const fn md5_const(input: &[u8]) -> [u8; 16] {
+    let mut state = INITIAL_STATE;
+    let mut buffer = [0; 64];
+    let mut block = [0; 16];
+
+    // the padded message consists of the input, the padding and the 8 bytes length
+    let len = input.len();
+    let pad_len = if len % 64 < 56 {
+        56 - len % 64
+    } else {
+        120 - len % 64
+    };
+    let len_bits = ((len as u64) << 3).to_le_bytes();
+
+    let mut i = 0;
+    while i < len + pad_len + 8 {
+        buffer[i % 64] = if i < len {
+            input[i]
+        } else if i < len + pad_len {
+            PADDING[i - len]
+        } else {
+            len_bits[i - len - pad_len]
+        };
+        i += 1;
+        if i % 64 == 0 {
+            decode(&buffer, &mut block);
+            compress(&mut state, &block);
+        }
+    }
+
+    encode(&state)
+}
+
+const INITIAL_STATE: [u32; 4] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+
+/// MD5 hash state, wiped on drop
+#[derive(Clone)]
 pub struct Md5 {
     state: [u32; 4],
     count: [u32; 2],
@@ -18,7 +70,7 @@ pub struct Md5 {
 impl Md5 {
     pub fn new() -> Self {
         Self {
-            state: [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476],
+            state: INITIAL_STATE,
             count: [0, 0],
             buffer: [0; 64],
         }
@@ -44,11 +96,12 @@ impl Md5 {
                 idx = 0;
             }
         }
+        block.zeroize();
 
         self
     }
 
-    pub fn digest(mut self) -> [u8; 16] {
+    pub fn digest(mut self) -> Secret<[u8; 16]> {
         let len_bits: [u8; 8] = encode(&self.count); // save length
 
         let idx = (self.count[0] >> 3 & 0x3f) as usize;
@@ -57,7 +110,72 @@ impl Md5 {
 
         self.update(&len_bits); // append length
 
-        encode(&self.state)
+        Secret::new(encode(&self.state))
+    }
+
+    /// Exports the state of the partially computed hash, see [`crate::midstate`]
+    pub fn export_midstate(&self) -> [u8; MIDSTATE_SIZE] {
+        let mut bytes = [0; MIDSTATE_SIZE];
+        bytes[0] = midstate::VERSION;
+        bytes[1] = midstate::ALGORITHM_MD5;
+        for (dst, word) in bytes[2..18].chunks_exact_mut(4).zip(&self.state) {
+            dst.copy_from_slice(&word.to_be_bytes());
+        }
+        let len_bits = (self.count[1] as u64) << 32 | self.count[0] as u64;
+        bytes[18..26].copy_from_slice(&len_bits.to_be_bytes());
+        let idx = (self.count[0] >> 3 & 0x3f) as usize;
+        bytes[26..26 + idx].copy_from_slice(&self.buffer[..idx]);
+        bytes
+    }
+
+    /// Continues a partially computed hash from a midstate exported by [`Md5::export_midstate`]
+    pub fn import_midstate(bytes: &[u8; MIDSTATE_SIZE]) -> Result<Self, MidstateError> {
+        midstate::check_header(bytes, midstate::ALGORITHM_MD5)?;
+
+        let mut state = [0; 4];
+        for (word, src) in state.iter_mut().zip(bytes[2..18].chunks_exact(4)) {
+            *word = u32::from_be_bytes(src.try_into().unwrap());
+        }
+        let len_bits = u64::from_be_bytes(bytes[18..26].try_into().unwrap());
+        let idx = (len_bits >> 3 & 0x3f) as usize;
+        if len_bits % 8 != 0 || bytes[26 + idx..].iter().any(|&b| b != 0) {
+            return Err(MidstateError::Corrupted);
+        }
+
+        Ok(Self {
+            state,
+            count: [len_bits as u32, (len_bits >> 32) as u32],
+            buffer: bytes[26..].try_into().unwrap(),
+        })
+    }
+}
+
+impl Hasher for Md5 {
+    const OUTPUT_SIZE: usize = 16;
+    const BLOCK_SIZE: usize = 64;
+
+    type Output = Secret<[u8; 16]>;
+
+    fn update(&mut self, input: &[u8]) -> &mut Self {
+        Md5::update(self, input)
+    }
+
+    fn finalize(self) -> Self::Output {
+        self.digest()
+    }
+}
+
+impl Zeroize for Md5 {
+    fn zeroize(&mut self) {
+        self.state.zeroize();
+        self.count.zeroize();
+        self.buffer.zeroize();
+    }
+}
+
+impl Drop for Md5 {
+    fn drop(&mut self) {
+        self.zeroize();
     }
 }
 
@@ -67,7 +185,7 @@ impl Default for Md5 {
     }
 }
 
-fn compress(state: &mut [u32; 4], block: &[u32; 16]) {
+const fn compress(state: &mut [u32; 4], block: &[u32; 16]) {
     let mut a = state[0];
     let mut b = state[1];
     let mut c = state[2];
@@ -79,22 +197,22 @@ fn compress(state: &mut [u32; 4], block: &[u32; 16]) {
     const S13: u32 = 17;
     const S14: u32 = 22;
 
-    step(&mut a, b, c, d, block[0], S11, 0xd76aa478, f); // 1
-    step(&mut d, a, b, c, block[1], S12, 0xe8c7b756, f); // 2
-    step(&mut c, d, a, b, block[2], S13, 0x242070db, f); // 3
-    step(&mut b, c, d, a, block[3], S14, 0xc1bdceee, f); // 4
-    step(&mut a, b, c, d, block[4], S11, 0xf57c0faf, f); // 5
-    step(&mut d, a, b, c, block[5], S12, 0x4787c62a, f); // 6
-    step(&mut c, d, a, b, block[6], S13, 0xa8304613, f); // 7
-    step(&mut b, c, d, a, block[7], S14, 0xfd469501, f); // 8
-    step(&mut a, b, c, d, block[8], S11, 0x698098d8, f); // 9
-    step(&mut d, a, b, c, block[9], S12, 0x8b44f7af, f); // 10
-    step(&mut c, d, a, b, block[10], S13, 0xffff5bb1, f); // 11
-    step(&mut b, c, d, a, block[11], S14, 0x895cd7be, f); // 12
-    step(&mut a, b, c, d, block[12], S11, 0x6b901122, f); // 13
-    step(&mut d, a, b, c, block[13], S12, 0xfd987193, f); // 14
-    step(&mut c, d, a, b, block[14], S13, 0xa679438e, f); // 15
-    step(&mut b, c, d, a, block[15], S14, 0x49b40821, f); // 16
+    step(&mut a, b, f(b, c, d), block[0], S11, 0xd76aa478); // 1
+    step(&mut d, a, f(a, b, c), block[1], S12, 0xe8c7b756); // 2
+    step(&mut c, d, f(d, a, b), block[2], S13, 0x242070db); // 3
+    step(&mut b, c, f(c, d, a), block[3], S14, 0xc1bdceee); // 4
+    step(&mut a, b, f(b, c, d), block[4], S11, 0xf57c0faf); // 5
+    step(&mut d, a, f(a, b, c), block[5], S12, 0x4787c62a); // 6
+    step(&mut c, d, f(d, a, b), block[6], S13, 0xa8304613); // 7
+    step(&mut b, c, f(c, d, a), block[7], S14, 0xfd469501); // 8
+    step(&mut a, b, f(b, c, d), block[8], S11, 0x698098d8); // 9
+    step(&mut d, a, f(a, b, c), block[9], S12, 0x8b44f7af); // 10
+    step(&mut c, d, f(d, a, b), block[10], S13, 0xffff5bb1); // 11
+    step(&mut b, c, f(c, d, a), block[11], S14, 0x895cd7be); // 12
+    step(&mut a, b, f(b, c, d), block[12], S11, 0x6b901122); // 13
+    step(&mut d, a, f(a, b, c), block[13], S12, 0xfd987193); // 14
+    step(&mut c, d, f(d, a, b), block[14], S13, 0xa679438e); // 15
+    step(&mut b, c, f(c, d, a), block[15], S14, 0x49b40821); // 16
 
     // Round 2
     const S21: u32 = 5;
@@ -102,22 +220,22 @@ fn compress(state: &mut [u32; 4], block: &[u32; 16]) {
     const S23: u32 = 14;
     const S24: u32 = 20;
 
-    step(&mut a, b, c, d, block[1], S21, 0xf61e2562, g); // 17
-    step(&mut d, a, b, c, block[6], S22, 0xc040b340, g); // 18
-    step(&mut c, d, a, b, block[11], S23, 0x265e5a51, g); // 19
-    step(&mut b, c, d, a, block[0], S24, 0xe9b6c7aa, g); // 20
-    step(&mut a, b, c, d, block[5], S21, 0xd62f105d, g); // 21
-    step(&mut d, a, b, c, block[10], S22, 0x2441453, g); // 22
-    step(&mut c, d, a, b, block[15], S23, 0xd8a1e681, g); // 23
-    step(&mut b, c, d, a, block[4], S24, 0xe7d3fbc8, g); // 24
-    step(&mut a, b, c, d, block[9], S21, 0x21e1cde6, g); // 25
-    step(&mut d, a, b, c, block[14], S22, 0xc33707d6, g); // 26
-    step(&mut c, d, a, b, block[3], S23, 0xf4d50d87, g); // 27
-    step(&mut b, c, d, a, block[8], S24, 0x455a14ed, g); // 28
-    step(&mut a, b, c, d, block[13], S21, 0xa9e3e905, g); // 29
-    step(&mut d, a, b, c, block[2], S22, 0xfcefa3f8, g); // 30
-    step(&mut c, d, a, b, block[7], S23, 0x676f02d9, g); // 31
-    step(&mut b, c, d, a, block[12], S24, 0x8d2a4c8a, g); // 32
+    step(&mut a, b, g(b, c, d), block[1], S21, 0xf61e2562); // 17
+    step(&mut d, a, g(a, b, c), block[6], S22, 0xc040b340); // 18
+    step(&mut c, d, g(d, a, b), block[11], S23, 0x265e5a51); // 19
+    step(&mut b, c, g(c, d, a), block[0], S24, 0xe9b6c7aa); // 20
+    step(&mut a, b, g(b, c, d), block[5], S21, 0xd62f105d); // 21
+    step(&mut d, a, g(a, b, c), block[10], S22, 0x2441453); // 22
+    step(&mut c, d, g(d, a, b), block[15], S23, 0xd8a1e681); // 23
+    step(&mut b, c, g(c, d, a), block[4], S24, 0xe7d3fbc8); // 24
+    step(&mut a, b, g(b, c, d), block[9], S21, 0x21e1cde6); // 25
+    step(&mut d, a, g(a, b, c), block[14], S22, 0xc33707d6); // 26
+    step(&mut c, d, g(d, a, b), block[3], S23, 0xf4d50d87); // 27
+    step(&mut b, c, g(c, d, a), block[8], S24, 0x455a14ed); // 28
+    step(&mut a, b, g(b, c, d), block[13], S21, 0xa9e3e905); // 29
+    step(&mut d, a, g(a, b, c), block[2], S22, 0xfcefa3f8); // 30
+    step(&mut c, d, g(d, a, b), block[7], S23, 0x676f02d9); // 31
+    step(&mut b, c, g(c, d, a), block[12], S24, 0x8d2a4c8a); // 32
 
     // Round 3
     const S31: u32 = 4;
@@ -125,22 +243,22 @@ fn compress(state: &mut [u32; 4], block: &[u32; 16]) {
     const S33: u32 = 16;
     const S34: u32 = 23;
 
-    step(&mut a, b, c, d, block[5], S31, 0xfffa3942, h); // 33
-    step(&mut d, a, b, c, block[8], S32, 0x8771f681, h); // 34
-    step(&mut c, d, a, b, block[11], S33, 0x6d9d6122, h); // 35
-    step(&mut b, c, d, a, block[14], S34, 0xfde5380c, h); // 36
-    step(&mut a, b, c, d, block[1], S31, 0xa4beea44, h); // 37
-    step(&mut d, a, b, c, block[4], S32, 0x4bdecfa9, h); // 38
-    step(&mut c, d, a, b, block[7], S33, 0xf6bb4b60, h); // 39
-    step(&mut b, c, d, a, block[10], S34, 0xbebfbc70, h); // 40
-    step(&mut a, b, c, d, block[13], S31, 0x289b7ec6, h); // 41
-    step(&mut d, a, b, c, block[0], S32, 0xeaa127fa, h); // 42
-    step(&mut c, d, a, b, block[3], S33, 0xd4ef3085, h); // 43
-    step(&mut b, c, d, a, block[6], S34, 0x4881d05, h); // 44
-    step(&mut a, b, c, d, block[9], S31, 0xd9d4d039, h); // 45
-    step(&mut d, a, b, c, block[12], S32, 0xe6db99e5, h); // 46
-    step(&mut c, d, a, b, block[15], S33, 0x1fa27cf8, h); // 47
-    step(&mut b, c, d, a, block[2], S34, 0xc4ac5665, h); // 48
+    step(&mut a, b, h(b, c, d), block[5], S31, 0xfffa3942); // 33
+    step(&mut d, a, h(a, b, c), block[8], S32, 0x8771f681); // 34
+    step(&mut c, d, h(d, a, b), block[11], S33, 0x6d9d6122); // 35
+    step(&mut b, c, h(c, d, a), block[14], S34, 0xfde5380c); // 36
+    step(&mut a, b, h(b, c, d), block[1], S31, 0xa4beea44); // 37
+    step(&mut d, a, h(a, b, c), block[4], S32, 0x4bdecfa9); // 38
+    step(&mut c, d, h(d, a, b), block[7], S33, 0xf6bb4b60); // 39
+    step(&mut b, c, h(c, d, a), block[10], S34, 0xbebfbc70); // 40
+    step(&mut a, b, h(b, c, d), block[13], S31, 0x289b7ec6); // 41
+    step(&mut d, a, h(a, b, c), block[0], S32, 0xeaa127fa); // 42
+    step(&mut c, d, h(d, a, b), block[3], S33, 0xd4ef3085); // 43
+    step(&mut b, c, h(c, d, a), block[6], S34, 0x4881d05); // 44
+    step(&mut a, b, h(b, c, d), block[9], S31, 0xd9d4d039); // 45
+    step(&mut d, a, h(a, b, c), block[12], S32, 0xe6db99e5); // 46
+    step(&mut c, d, h(d, a, b), block[15], S33, 0x1fa27cf8); // 47
+    step(&mut b, c, h(c, d, a), block[2], S34, 0xc4ac5665); // 48
 
     // Round 4
     const S41: u32 = 6;
@@ -148,22 +266,22 @@ fn compress(state: &mut [u32; 4], block: &[u32; 16]) {
     const S43: u32 = 15;
     const S44: u32 = 21;
 
-    step(&mut a, b, c, d, block[0], S41, 0xf4292244, i); // 49
-    step(&mut d, a, b, c, block[7], S42, 0x432aff97, i); // 50
-    step(&mut c, d, a, b, block[14], S43, 0xab9423a7, i); // 51
-    step(&mut b, c, d, a, block[5], S44, 0xfc93a039, i); // 52
-    step(&mut a, b, c, d, block[12], S41, 0x655b59c3, i); // 53
-    step(&mut d, a, b, c, block[3], S42, 0x8f0ccc92, i); // 54
-    step(&mut c, d, a, b, block[10], S43, 0xffeff47d, i); // 55
-    step(&mut b, c, d, a, block[1], S44, 0x85845dd1, i); // 56
-    step(&mut a, b, c, d, block[8], S41, 0x6fa87e4f, i); // 57
-    step(&mut d, a, b, c, block[15], S42, 0xfe2ce6e0, i); // 58
-    step(&mut c, d, a, b, block[6], S43, 0xa3014314, i); // 59
-    step(&mut b, c, d, a, block[13], S44, 0x4e0811a1, i); // 60
-    step(&mut a, b, c, d, block[4], S41, 0xf7537e82, i); // 61
-    step(&mut d, a, b, c, block[11], S42, 0xbd3af235, i); // 62
-    step(&mut c, d, a, b, block[2], S43, 0x2ad7d2bb, i); // 63
-    step(&mut b, c, d, a, block[9], S44, 0xeb86d391, i); // 64
+    step(&mut a, b, i(b, c, d), block[0], S41, 0xf4292244); // 49
+    step(&mut d, a, i(a, b, c), block[7], S42, 0x432aff97); // 50
+    step(&mut c, d, i(d, a, b), block[14], S43, 0xab9423a7); // 51
+    step(&mut b, c, i(c, d, a), block[5], S44, 0xfc93a039); // 52
+    step(&mut a, b, i(b, c, d), block[12], S41, 0x655b59c3); // 53
+    step(&mut d, a, i(a, b, c), block[3], S42, 0x8f0ccc92); // 54
+    step(&mut c, d, i(d, a, b), block[10], S43, 0xffeff47d); // 55
+    step(&mut b, c, i(c, d, a), block[1], S44, 0x85845dd1); // 56
+    step(&mut a, b, i(b, c, d), block[8], S41, 0x6fa87e4f); // 57
+    step(&mut d, a, i(a, b, c), block[15], S42, 0xfe2ce6e0); // 58
+    step(&mut c, d, i(d, a, b), block[6], S43, 0xa3014314); // 59
+    step(&mut b, c, i(c, d, a), block[13], S44, 0x4e0811a1); // 60
+    step(&mut a, b, i(b, c, d), block[4], S41, 0xf7537e82); // 61
+    step(&mut d, a, i(a, b, c), block[11], S42, 0xbd3af235); // 62
+    step(&mut c, d, i(d, a, b), block[2], S43, 0x2ad7d2bb); // 63
+    step(&mut b, c, i(c, d, a), block[9], S44, 0xeb86d391); // 64
 
     state[0] = state[0].wrapping_add(a);
     state[1] = state[1].wrapping_add(b);
@@ -171,57 +289,52 @@ fn compress(state: &mut [u32; 4], block: &[u32; 16]) {
     state[3] = state[3].wrapping_add(d);
 }
 
-fn encode<const N: usize>(input: &[u32]) -> [u8; N] {
+const fn encode<const N: usize>(input: &[u32]) -> [u8; N] {
     let mut res = [0; N];
-    for i in 0..input.len() {
+    let mut i = 0;
+    while i < input.len() {
         let bytes = u32::to_le_bytes(input[i]);
         res[4 * i] = bytes[0];
         res[4 * i + 1] = bytes[1];
         res[4 * i + 2] = bytes[2];
         res[4 * i + 3] = bytes[3];
+        i += 1;
     }
     res
 }
 
-fn decode(bytes: &[u8; 64], res: &mut [u32; 16]) {
-    for i in 0..16 {
+const fn decode(bytes: &[u8; 64], res: &mut [u32; 16]) {
+    let mut i = 0;
+    while i < 16 {
         res[i] = u32::from_le_bytes([
             bytes[4 * i],
             bytes[4 * i + 1],
             bytes[4 * i + 2],
             bytes[4 * i + 3],
         ]);
+        i += 1;
     }
 }
 
-fn f(x: u32, y: u32, z: u32) -> u32 {
+const fn f(x: u32, y: u32, z: u32) -> u32 {
     (x & y) | ((!x) & z)
 }
 
-fn g(x: u32, y: u32, z: u32) -> u32 {
+const fn g(x: u32, y: u32, z: u32) -> u32 {
     (x & z) | (y & (!z))
 }
 
-fn h(x: u32, y: u32, z: u32) -> u32 {
+const fn h(x: u32, y: u32, z: u32) -> u32 {
     x ^ y ^ z
 }
 
-fn i(x: u32, y: u32, z: u32) -> u32 {
+const fn i(x: u32, y: u32, z: u32) -> u32 {
     y ^ (x | !z)
 }
 
-#[allow(clippy::too_many_arguments)]
-fn step(
-    a: &mut u32,
-    b: u32,
-    c: u32,
-    d: u32,
-    x: u32,
-    s: u32,
-    ac: u32,
-    f: impl FnOnce(u32, u32, u32) -> u32,
-) {
-    *a = a.wrapping_add(f(b, c, d).wrapping_add(x).wrapping_add(ac));
+/// Step operation with `fbcd` being the round function applied to `b`, `c` and `d`
+const fn step(a: &mut u32, b: u32, fbcd: u32, x: u32, s: u32, ac: u32) {
+    *a = a.wrapping_add(fbcd.wrapping_add(x).wrapping_add(ac));
     *a = a.rotate_left(s);
     *a = a.wrapping_add(b);
 }
@@ -238,6 +351,8 @@ mod tests {
     use super::*;
     use crate::hex;
 
+    use quickcheck_macros::quickcheck;
+
     #[test]
     fn test_md5() {
         assert_eq!(md5(b""), hex("d41d8cd98f00b204e9800998ecf8427e").unwrap());
@@ -264,4 +379,81 @@ mod tests {
             hex("57edf4a22be3c955ac49da2e2107b67a").unwrap()
         );
     }
+
+    #[test]
+    fn test_const_md5() {
+        const EMPTY: [u8; 16] = md5_const(b"");
+        const ABC: [u8; 16] = md5_const(b"abc");
+        const LONG: [u8; 16] = md5_const(&[0x5a; 1000]);
+
+        assert_eq!(EMPTY, hex("d41d8cd98f00b204e9800998ecf8427e").unwrap());
+        assert_eq!(ABC, hex("900150983cd24fb0d6963f7d28e17f72").unwrap());
+        assert_eq!(md5(&[0x5a; 1000]), LONG);
+    }
+
+    #[quickcheck]
+    fn test_const_md5_matches(input: Vec<u8>) {
+        assert_eq!(md5(&input), md5_const(&input));
+    }
+
+    #[quickcheck]
+    fn test_md5_matches_incremental(input: Vec<u8>, split: usize) {
+        let (head, tail) = input.split_at(split % (input.len() + 1));
+        let mut state = Md5::new();
+        state.update(head).update(tail);
+        assert_eq!(md5(&input), state.digest());
+    }
+
+    #[quickcheck]
+    fn test_midstate(input: Vec<u8>, split: usize) {
+        let (head, tail) = input.split_at(split % (input.len() + 1));
+        let mut state = Md5::new();
+        state.update(head);
+        let bytes = state.export_midstate();
+        drop(state);
+
+        let mut state = Md5::import_midstate(&bytes).unwrap();
+        state.update(tail);
+        assert_eq!(state.digest(), md5(&input));
+    }
+
+    #[test]
+    fn test_midstate_layout() {
+        let mut state = Md5::new();
+        state.update(&[0xff; 65]);
+        let bytes = state.export_midstate();
+        assert_eq!(bytes[0..2], [1, 1]);
+        assert_eq!(bytes[18..26], (65_u64 * 8).to_be_bytes());
+        assert_eq!(bytes[26], 0xff);
+        assert!(bytes[27..].iter().all(|&b| b == 0));
+
+        let mut corrupted = bytes;
+        corrupted[27] = 1;
+        assert_eq!(
+            Md5::import_midstate(&corrupted).err(),
+            Some(MidstateError::Corrupted)
+        );
+        let mut newer = bytes;
+        newer[0] = 2;
+        assert_eq!(
+            Md5::import_midstate(&newer).err(),
+            Some(MidstateError::UnsupportedVersion(2))
+        );
+        let mut sha1 = bytes;
+        sha1[1] = 2;
+        assert_eq!(
+            Md5::import_midstate(&sha1).err(),
+            Some(MidstateError::WrongAlgorithm(2))
+        );
+    }
+
+    #[test]
+    fn test_zeroize() {
+        let mut state = Md5::new();
+        state.update(b"secret");
+        state.zeroize();
+        assert_eq!(state.state, [0; 4]);
+        assert_eq!(state.count, [0; 2]);
+        assert_eq!(state.buffer, [0; 64]);
+    }
 }
@@ -0,0 +1,113 @@
+//! Export and import of partially computed hashes
+//!
+//! A midstate is a fixed size byte array, which can be persisted and later imported to continue
+//! hashing, e.g. after a process restart. The layout is stable:
+//!
+//! | offset | size | content                                     |
+//! |--------|------|---------------------------------------------|
+//! | 0      | 1    | format version, currently 1                 |
+//! | 1      | 1    | algorithm: 1 = MD5, 2 = SHA-1               |
+//! | 2      |      | algorithm specific state, see below         |
+//!
+//! All integers are stored in big-endian byte order. The algorithm specific state is
+//!
+//! * MD5 ([`crate::md5::MIDSTATE_SIZE`] bytes in total): the 4 state words, the message length
+//!   in bits as `u64` and the 64 bytes buffer,
+//! * SHA-1 ([`crate::sha1::MIDSTATE_SIZE`] bytes in total): the 5 state words, the message
+//!   length in bytes as `u64`, the number of buffered bytes as `u8` and the 64 bytes block.
+//!
+//! Buffer bytes which are not part of the message are exported as zeros.
+
+use core::fmt;
+
+/// Current version of the midstate format
+pub const VERSION: u8 = 1;
+
+pub(crate) const ALGORITHM_MD5: u8 = 1;
+pub(crate) const ALGORITHM_SHA1: u8 = 2;
+
+/// Error for midstates which cannot be imported
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MidstateError {
+    /// The midstate has a format version which is not supported
+    UnsupportedVersion(u8),
+    /// The midstate was exported from another hash algorithm
+    WrongAlgorithm(u8),
+    /// The fields of the midstate are inconsistent
+    Corrupted,
+}
+
+impl fmt::Display for MidstateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MidstateError::UnsupportedVersion(version) => {
+                write!(f, "unsupported midstate version: {}", version)
+            }
+            MidstateError::WrongAlgorithm(algorithm) => {
+                write!(f, "midstate of another hash algorithm: {}", algorithm)
+            }
+            MidstateError::Corrupted => f.write_str("corrupted midstate"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MidstateError {}
+
+/// Checks the version and algorithm of `midstate`
+pub(crate) fn check_header(midstate: &[u8], algorithm: u8) -> Result<(), MidstateError> {
+    if midstate[0] != VERSION {
+        return Err(MidstateError::UnsupportedVersion(midstate[0]));
+    }
+    if midstate[1] != algorithm {
+        return Err(MidstateError::WrongAlgorithm(midstate[1]));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hex;
+    use crate::md5::{self, Md5};
+    use crate::sha1::{self, Sha1};
+
+    // Midstates after hashing "abc", built by hand from the initial states of RFC 1321 and
+    // RFC 3174, so that the version 1 format cannot change unnoticed.
+    const MD5_ABC: &str = "0101 67452301 efcdab89 98badcfe 10325476 0000000000000018
+        61626300000000000000000000000000 00000000000000000000000000000000
+        00000000000000000000000000000000 00000000000000000000000000000000";
+    const SHA1_ABC: &str = "0102 67452301 efcdab89 98badcfe 10325476 c3d2e1f0
+        0000000000000003 03
+        61626300000000000000000000000000 00000000000000000000000000000000
+        00000000000000000000000000000000 00000000000000000000000000000000";
+
+    #[test]
+    fn test_md5_golden() {
+        let midstate: [u8; md5::MIDSTATE_SIZE] = hex(MD5_ABC).unwrap();
+        let mut state = Md5::new();
+        state.update(b"abc");
+        assert_eq!(state.export_midstate(), midstate);
+
+        let mut state = Md5::import_midstate(&midstate).unwrap();
+        state.update(b"defghijklmnopqrstuvwxyz");
+        assert_eq!(
+            state.digest(),
+            hex("c3fcd3d76192e4007dfb496cca67e13b").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_sha1_golden() {
+        let midstate: [u8; sha1::MIDSTATE_SIZE] = hex(SHA1_ABC).unwrap();
+        let mut state = Sha1::new();
+        state.update(b"abc");
+        assert_eq!(state.export_midstate(), midstate);
+
+        let mut state = Sha1::import_midstate(&midstate).unwrap();
+        state.update(b"dbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq");
+        assert_eq!(
+            state.digest(),
+            hex("84983e441c3bd26ebaae4aa1f95129e5e54670f1").unwrap()
+        );
+    }
+}
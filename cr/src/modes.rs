@@ -0,0 +1,194 @@
+//! Block cipher modes of operation
+//!
+//! https://nvlpubs.nist.gov/nistpubs/Legacy/SP/nistspecialpublication800-38a.pdf
+//!
+//! The modes are generic over [`BlockCipher`] and work in place on byte slices, so they neither
+//! allocate nor copy the message. Messages of arbitrary length are padded with [`pkcs7_pad`].
+//!
+//! The modes keep blocks on the stack and support block sizes of up to 16 bytes, the largest
+//! block size of the ciphers of the crate; they panic for ciphers with larger blocks.
+
+pub mod cbc;
+pub mod ccm;
+pub mod cfb;
+pub mod ctr;
+pub mod ecb;
+pub mod gcm;
+pub mod ofb;
+pub mod xts;
+
+use crate::ct::{ConstantTimeEq, ConstantTimeLess};
+use crate::zeroize::Zeroize;
+use crate::BlockCipher;
+
+use core::fmt;
+
+/// Largest block size of the ciphers of the crate
+const MAX_BLOCK_SIZE: usize = 16;
+
+/// Error for messages which cannot be encrypted or decrypted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModeError {
+    /// The message is not a whole number of blocks, the buffer is too small for the padding, or
+    /// the nonce or tag has an unsupported length
+    InvalidLength,
+    /// The padding of the decrypted message is malformed
+    ///
+    /// The error does not tell which byte of the padding was wrong, and the padding is checked
+    /// in constant time, so that it cannot be used as a padding oracle.
+    InvalidPadding,
+    /// The authentication tag does not match, i.e. the message or its associated data was
+    /// modified
+    InvalidTag,
+    /// The two keys of XTS are equal
+    EqualKeys,
+}
+
+impl fmt::Display for ModeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ModeError::InvalidLength => f.write_str("invalid message length"),
+            ModeError::InvalidPadding => f.write_str("invalid padding"),
+            ModeError::InvalidTag => f.write_str("authentication failed"),
+            ModeError::EqualKeys => f.write_str("equal keys"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ModeError {}
+
+/// Length of a message of `len` bytes padded with [`pkcs7_pad`]
+pub fn pkcs7_padded_len(len: usize, block_size: usize) -> usize {
+    (len / block_size + 1) * block_size
+}
+
+/// Pads the message `buf[..len]` to whole blocks and returns the padded message
+///
+/// Each padding byte is the number of padding bytes; a message of whole blocks is padded by a
+/// full block. `buf` must hold at least [`pkcs7_padded_len`] bytes.
+pub fn pkcs7_pad(buf: &mut [u8], len: usize, block_size: usize) -> Result<&mut [u8], ModeError> {
+    assert!(0 < block_size && block_size < 256, "invalid block size");
+    let padded_len = pkcs7_padded_len(len, block_size);
+    let buf = buf.get_mut(..padded_len).ok_or(ModeError::InvalidLength)?;
+    for b in &mut buf[len..] {
+        *b = (padded_len - len) as u8;
+    }
+    Ok(buf)
+}
+
+/// Checks the padding of `buf` and returns the message without padding
+///
+/// The padding is checked in constant time. `buf` is left unchanged if the padding is invalid;
+/// the `decrypt_padded` functions of the modes wipe the decrypted message in this case.
+#[allow(clippy::manual_is_multiple_of)] // `is_multiple_of` needs Rust 1.87
+pub fn pkcs7_unpad(buf: &[u8], block_size: usize) -> Result<&[u8], ModeError> {
+    assert!(0 < block_size && block_size < 256, "invalid block size");
+    if buf.is_empty() || buf.len() % block_size != 0 {
+        return Err(ModeError::InvalidLength);
+    }
+
+    let last_block = &buf[buf.len() - block_size..];
+    let pad_len = last_block[block_size - 1];
+    let mut invalid = pad_len.ct_eq(&0) | (block_size as u8).ct_lt(&pad_len);
+    for (i, b) in last_block.iter().rev().enumerate() {
+        let in_padding = (i as u8).ct_lt(&pad_len);
+        invalid = invalid | (in_padding & !b.ct_eq(&pad_len));
+    }
+
+    if bool::from(invalid) {
+        Err(ModeError::InvalidPadding)
+    } else {
+        Ok(&buf[..buf.len() - pad_len as usize])
+    }
+}
+
+/// Removes the padding of the decrypted message `buf`, which is wiped if the padding is invalid
+fn unpad_decrypted(buf: &mut [u8], block_size: usize) -> Result<&[u8], ModeError> {
+    match pkcs7_unpad(buf, block_size).map(<[u8]>::len) {
+        Ok(len) => Ok(&buf[..len]),
+        Err(err) => {
+            buf.zeroize();
+            Err(err)
+        }
+    }
+}
+
+fn xor(dst: &mut [u8], src: &[u8]) {
+    for (d, s) in dst.iter_mut().zip(src) {
+        *d ^= s;
+    }
+}
+
+fn check_block_size<C: BlockCipher>() {
+    assert!(
+        C::BLOCK_SIZE <= MAX_BLOCK_SIZE,
+        "block sizes of more than {} bytes are not supported",
+        MAX_BLOCK_SIZE
+    );
+}
+
+#[allow(clippy::manual_is_multiple_of)] // `is_multiple_of` needs Rust 1.87
+fn check_blocks<C: BlockCipher>(buf: &[u8]) -> Result<(), ModeError> {
+    if buf.len() % C::BLOCK_SIZE == 0 {
+        Ok(())
+    } else {
+        Err(ModeError::InvalidLength)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Identity cipher with 32 bytes blocks
+    struct WideBlocks;
+
+    impl BlockCipher for WideBlocks {
+        const BLOCK_SIZE: usize = 32;
+        const KEY_SIZE: usize = 0;
+
+        fn new(_: &[u8]) -> Self {
+            WideBlocks
+        }
+
+        fn encrypt_block(&self, _: &mut [u8]) {}
+
+        fn decrypt_block(&self, _: &mut [u8]) {}
+    }
+
+    #[test]
+    #[should_panic(expected = "block sizes of more than 16 bytes are not supported")]
+    fn test_wide_blocks() {
+        ofb::Ofb::new(&WideBlocks, &[0; 32]);
+    }
+
+    #[test]
+    fn test_pkcs7_pad() {
+        let mut buf = [0xaa; 16];
+        assert_eq!(
+            pkcs7_pad(&mut buf, 5, 8).unwrap(),
+            [0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 3, 3, 3]
+        );
+        assert_eq!(pkcs7_pad(&mut buf, 8, 8).unwrap()[8..], [8; 8]);
+        assert_eq!(pkcs7_pad(&mut buf, 0, 16).unwrap(), [16; 16]);
+        assert_eq!(pkcs7_pad(&mut buf, 16, 16), Err(ModeError::InvalidLength));
+    }
+
+    #[test]
+    fn test_pkcs7_unpad() {
+        assert_eq!(
+            pkcs7_unpad(&[1, 2, 3, 4, 5, 3, 3, 3], 8),
+            Ok(&[1, 2, 3, 4, 5][..])
+        );
+        assert_eq!(pkcs7_unpad(&[8; 8], 8), Ok(&[][..]));
+        assert_eq!(
+            pkcs7_unpad(&[1, 2, 3, 4, 5, 2, 3, 3], 8),
+            Err(ModeError::InvalidPadding)
+        );
+        assert_eq!(pkcs7_unpad(&[0; 8], 8), Err(ModeError::InvalidPadding));
+        assert_eq!(pkcs7_unpad(&[9; 8], 8), Err(ModeError::InvalidPadding));
+        assert_eq!(pkcs7_unpad(&[1; 7], 8), Err(ModeError::InvalidLength));
+        assert_eq!(pkcs7_unpad(&[], 8), Err(ModeError::InvalidLength));
+    }
+}
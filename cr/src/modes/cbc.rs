@@ -0,0 +1,150 @@
+//! Cipher block chaining (CBC) mode
+//!
+//! Each plaintext block is XORed with the previous ciphertext block, or with the IV for the
+//! first block, before it is encrypted. The IV must be unpredictable, e.g. random.
+
+use super::{
+    check_block_size, check_blocks, pkcs7_pad, unpad_decrypted, xor, ModeError, MAX_BLOCK_SIZE,
+};
+use crate::BlockCipher;
+
+/// Encrypts `buf` in place, which must be a whole number of blocks
+///
+/// Panics if `iv` is not a single block.
+pub fn encrypt<C: BlockCipher>(cipher: &C, iv: &[u8], buf: &mut [u8]) -> Result<(), ModeError> {
+    assert_eq!(iv.len(), C::BLOCK_SIZE, "invalid IV size");
+    check_blocks::<C>(buf)?;
+    let mut prev = iv;
+    for block in buf.chunks_exact_mut(C::BLOCK_SIZE) {
+        xor(block, prev);
+        cipher.encrypt_block(block);
+        prev = block;
+    }
+    Ok(())
+}
+
+/// Decrypts `buf` in place, which must be a whole number of blocks
+///
+/// Panics if `iv` is not a single block or the block size is larger than 16 bytes.
+pub fn decrypt<C: BlockCipher>(cipher: &C, iv: &[u8], buf: &mut [u8]) -> Result<(), ModeError> {
+    check_block_size::<C>();
+    assert_eq!(iv.len(), C::BLOCK_SIZE, "invalid IV size");
+    check_blocks::<C>(buf)?;
+    let mut prev = [0; MAX_BLOCK_SIZE];
+    let mut ciphertext = [0; MAX_BLOCK_SIZE];
+    prev[..C::BLOCK_SIZE].copy_from_slice(iv);
+    for block in buf.chunks_exact_mut(C::BLOCK_SIZE) {
+        ciphertext[..C::BLOCK_SIZE].copy_from_slice(block);
+        cipher.decrypt_block(block);
+        xor(block, &prev[..C::BLOCK_SIZE]);
+        prev = ciphertext;
+    }
+    Ok(())
+}
+
+/// Pads the message `buf[..len]` with PKCS#7 and encrypts it in place
+///
+/// `buf` must hold at least [`super::pkcs7_padded_len`] bytes. Returns the ciphertext.
+pub fn encrypt_padded<'a, C: BlockCipher>(
+    cipher: &C,
+    iv: &[u8],
+    buf: &'a mut [u8],
+    len: usize,
+) -> Result<&'a [u8], ModeError> {
+    let buf = pkcs7_pad(buf, len, C::BLOCK_SIZE)?;
+    encrypt(cipher, iv, buf)?;
+    Ok(buf)
+}
+
+/// Decrypts `buf` in place and removes the PKCS#7 padding; returns the plaintext
+///
+/// If the padding is invalid, `buf` is wiped, so that the decrypted message is not left behind.
+pub fn decrypt_padded<'a, C: BlockCipher>(
+    cipher: &C,
+    iv: &[u8],
+    buf: &'a mut [u8],
+) -> Result<&'a [u8], ModeError> {
+    decrypt(cipher, iv, buf)?;
+    unpad_decrypted(buf, C::BLOCK_SIZE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes::{Aes128, Aes192, Aes256};
+    use crate::des::Des;
+    use crate::hex_vec;
+    use crate::twofish::Twofish;
+
+    use quickcheck_macros::quickcheck;
+
+    // SP 800-38A, appendix F.2
+    const IV: &str = "000102030405060708090a0b0c0d0e0f";
+    const PLAINTEXT: &str = "6bc1bee22e409f96e93d7e117393172a ae2d8a571e03ac9c9eb76fac45af8e51
+                             30c81c46a35ce411e5fbc1191a0a52ef f69f2445df4f9b17ad2b417be66c3710";
+
+    fn check_vector<C: BlockCipher>(key: &str, ciphertext: &str) {
+        let cipher = C::new(&hex_vec(key));
+        let iv = hex_vec(IV);
+        let mut buf = hex_vec(PLAINTEXT);
+        encrypt(&cipher, &iv, &mut buf).unwrap();
+        assert_eq!(buf, hex_vec(ciphertext));
+        decrypt(&cipher, &iv, &mut buf).unwrap();
+        assert_eq!(buf, hex_vec(PLAINTEXT));
+    }
+
+    #[test]
+    fn test_cbc_aes128() {
+        check_vector::<Aes128>(
+            "2b7e151628aed2a6abf7158809cf4f3c",
+            "7649abac8119b246cee98e9b12e9197d 5086cb9b507219ee95db113a917678b2
+             73bed6b8e3c1743b7116e69e22229516 3ff1caa1681fac09120eca307586e1a7",
+        );
+    }
+
+    #[test]
+    fn test_cbc_aes192() {
+        check_vector::<Aes192>(
+            "8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b",
+            "4f021db243bc633d7178183a9fa071e8 b4d9ada9ad7dedf4e5e738763f69145a
+             571b242012fb7ae07fa9baac3df102e0 08b0e27988598881d920a9e64f5615cd",
+        );
+    }
+
+    #[test]
+    fn test_cbc_aes256() {
+        check_vector::<Aes256>(
+            "603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4",
+            "f58c4c04d6e5f1ba779eabfb5f7bfbd6 9cfc4e967edb808d679f777bc6702c7d
+             39f23369a9d9bacfa530e26304231461 b2eb05e2c39be9fcda6c19078c6a9d1b",
+        );
+    }
+
+    #[test]
+    fn test_invalid_padding() {
+        let cipher = <Aes128 as BlockCipher>::new(&[0; 16]);
+        let iv = [0; 16];
+        let mut buf = [0; 32];
+        encrypt_padded(&cipher, &iv, &mut buf, 20).unwrap();
+        buf[31] ^= 1;
+        assert_eq!(
+            decrypt_padded(&cipher, &iv, &mut buf),
+            Err(ModeError::InvalidPadding)
+        );
+        assert_eq!(buf, [0; 32]);
+    }
+
+    fn roundtrip<C: BlockCipher>(cipher: &C, iv: &[u8], msg: &[u8]) {
+        let mut buf = msg.to_vec();
+        buf.resize(super::super::pkcs7_padded_len(msg.len(), C::BLOCK_SIZE), 0);
+        encrypt_padded(cipher, iv, &mut buf, msg.len()).unwrap();
+        assert_eq!(decrypt_padded(cipher, iv, &mut buf).unwrap(), msg);
+    }
+
+    #[quickcheck]
+    fn test_padded_roundtrip(msg: Vec<u8>) {
+        roundtrip(&<Aes128 as BlockCipher>::new(&[1; 16]), &[4; 16], &msg);
+        roundtrip(&Des::new([2; 8]), &[5; 8], &msg);
+        roundtrip(&<Twofish as BlockCipher>::new(&[3; 32]), &[6; 16], &msg);
+    }
+}
@@ -0,0 +1,396 @@
+//! Counter with CBC-MAC (CCM) authenticated encryption
+//!
+//! https://nvlpubs.nist.gov/nistpubs/Legacy/SP/nistspecialpublication800-38c.pdf
+//!
+//! The associated data and the message are authenticated with CBC-MAC, and the message and the
+//! tag are encrypted in CTR mode (RFC 3610). CCM requires a cipher with 128-bit blocks, e.g.
+//! AES. The size L of the length field trades the maximum message length of 2^(8L) - 1 bytes
+//! against the nonce length of 15 - L bytes. A nonce must never be reused with the same key.
+
+use super::ctr::{CounterWidth, Ctr};
+use super::ModeError;
+use crate::ct::ConstantTimeEq;
+use crate::zeroize::Zeroize;
+use crate::BlockCipher;
+
+/// CCM authenticated encryption
+pub struct Ccm<'a, C: BlockCipher> {
+    cipher: &'a C,
+    tag_len: usize,
+    length_size: usize,
+}
+
+impl<'a, C: BlockCipher> Ccm<'a, C> {
+    /// Creates CCM with tags of `tag_len` bytes and a length field of `length_size` bytes
+    ///
+    /// Panics if the block size of the cipher is not 128 bits, `tag_len` is not one of 4, 6,
+    /// ..., 16, or `length_size` is not in 2..=8.
+    #[allow(clippy::manual_is_multiple_of)] // `is_multiple_of` needs Rust 1.87
+    pub fn new(cipher: &'a C, tag_len: usize, length_size: usize) -> Self {
+        assert_eq!(C::BLOCK_SIZE, 16, "CCM requires 128-bit blocks");
+        assert!(
+            (4..=16).contains(&tag_len) && tag_len % 2 == 0,
+            "invalid tag length"
+        );
+        assert!((2..=8).contains(&length_size), "invalid length field size");
+        Self {
+            cipher,
+            tag_len,
+            length_size,
+        }
+    }
+
+    /// Length of the nonce in bytes, i.e. 15 - L
+    pub fn nonce_len(&self) -> usize {
+        15 - self.length_size
+    }
+
+    /// Encrypts `buf` in place and writes the authentication tag to `tag`
+    ///
+    /// The nonce must have [`Ccm::nonce_len`] and the tag the configured length, and the
+    /// message length must fit into the length field.
+    pub fn encrypt(
+        &self,
+        nonce: &[u8],
+        aad: &[u8],
+        buf: &mut [u8],
+        tag: &mut [u8],
+    ) -> Result<(), ModeError> {
+        self.check_lengths(nonce, buf, tag)?;
+        let mut mac = self.mac(nonce, aad, buf);
+        let mut ctr = self.ctr(nonce);
+        ctr.apply_keystream(&mut mac)
+            .expect("message length is checked");
+        ctr.apply_keystream(buf).expect("message length is checked");
+        tag.copy_from_slice(&mac[..self.tag_len]);
+        mac.zeroize();
+        Ok(())
+    }
+
+    /// Decrypts `buf` in place and verifies `tag`
+    ///
+    /// The tag is compared in constant time. If it does not match, `buf` is wiped, since CCM
+    /// authenticates the plaintext and the message has to be decrypted for the check.
+    pub fn decrypt(
+        &self,
+        nonce: &[u8],
+        aad: &[u8],
+        buf: &mut [u8],
+        tag: &[u8],
+    ) -> Result<(), ModeError> {
+        self.check_lengths(nonce, buf, tag)?;
+        let mut ctr = self.ctr(nonce);
+        let mut tag_mask = [0; 16];
+        ctr.apply_keystream(&mut tag_mask)
+            .expect("message length is checked");
+        ctr.apply_keystream(buf).expect("message length is checked");
+
+        let mut mac = self.mac(nonce, aad, buf);
+        super::xor(&mut mac, &tag_mask);
+        let valid = bool::from(mac[..self.tag_len].ct_eq(tag));
+        mac.zeroize();
+        tag_mask.zeroize();
+        if !valid {
+            buf.zeroize();
+            return Err(ModeError::InvalidTag);
+        }
+        Ok(())
+    }
+
+    fn check_lengths(&self, nonce: &[u8], buf: &[u8], tag: &[u8]) -> Result<(), ModeError> {
+        let max_len = u64::MAX >> (64 - 8 * self.length_size);
+        if nonce.len() != self.nonce_len()
+            || tag.len() != self.tag_len
+            || buf.len() as u64 > max_len
+        {
+            return Err(ModeError::InvalidLength);
+        }
+        Ok(())
+    }
+
+    /// CTR mode starting at the counter block A0
+    ///
+    /// The counter never exceeds the length field, so a 64-bit counter does not carry into the
+    /// nonce.
+    fn ctr(&self, nonce: &[u8]) -> Ctr<'a, C> {
+        let mut a0 = [0; 16];
+        a0[0] = (self.length_size - 1) as u8;
+        a0[1..1 + nonce.len()].copy_from_slice(nonce);
+        let ctr = Ctr::new(self.cipher, &a0, CounterWidth::Bits64);
+        a0.zeroize();
+        ctr
+    }
+
+    /// Computes the CBC-MAC of the formatted nonce, associated data and message
+    fn mac(&self, nonce: &[u8], aad: &[u8], msg: &[u8]) -> [u8; 16] {
+        let mut b0 = [0; 16];
+        let flags = (!aad.is_empty() as usize) << 6
+            | ((self.tag_len - 2) / 2) << 3
+            | (self.length_size - 1);
+        b0[0] = flags as u8;
+        b0[1..1 + nonce.len()].copy_from_slice(nonce);
+        b0[1 + nonce.len()..]
+            .copy_from_slice(&(msg.len() as u64).to_be_bytes()[8 - self.length_size..]);
+
+        let mut mac = CbcMac::new(self.cipher);
+        mac.update(&b0);
+        if !aad.is_empty() {
+            let len = aad.len() as u64;
+            if len < (1 << 16) - (1 << 8) {
+                mac.update(&(len as u16).to_be_bytes());
+            } else if len < 1 << 32 {
+                mac.update(&[0xff, 0xfe]);
+                mac.update(&(len as u32).to_be_bytes());
+            } else {
+                mac.update(&[0xff, 0xff]);
+                mac.update(&len.to_be_bytes());
+            }
+            mac.update(aad);
+            mac.pad();
+        }
+        mac.update(msg);
+        mac.pad();
+        b0.zeroize();
+        mac.finalize()
+    }
+}
+
+/// CBC-MAC with zero IV over data padded to whole blocks, wiped on drop
+struct CbcMac<'a, C: BlockCipher> {
+    cipher: &'a C,
+    state: [u8; 16],
+    /// Number of bytes XORed into the current block
+    pos: usize,
+}
+
+impl<'a, C: BlockCipher> CbcMac<'a, C> {
+    fn new(cipher: &'a C) -> Self {
+        Self {
+            cipher,
+            state: [0; 16],
+            pos: 0,
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        for b in data {
+            self.state[self.pos] ^= b;
+            self.pos += 1;
+            if self.pos == 16 {
+                self.cipher.encrypt_block(&mut self.state);
+                self.pos = 0;
+            }
+        }
+    }
+
+    /// Pads the data absorbed so far with zeros to a whole block
+    fn pad(&mut self) {
+        if self.pos != 0 {
+            self.cipher.encrypt_block(&mut self.state);
+            self.pos = 0;
+        }
+    }
+
+    fn finalize(mut self) -> [u8; 16] {
+        let state = self.state;
+        self.zeroize();
+        state
+    }
+}
+
+impl<C: BlockCipher> Zeroize for CbcMac<'_, C> {
+    fn zeroize(&mut self) {
+        self.state.zeroize();
+        self.pos.zeroize();
+    }
+}
+
+impl<C: BlockCipher> Drop for CbcMac<'_, C> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes::Aes128;
+    use crate::encoding::HEX;
+    use crate::hex_vec;
+    use crate::twofish::Twofish;
+
+    use quickcheck_macros::quickcheck;
+
+    fn check_vector(
+        key: &str,
+        tag_len: usize,
+        nonce: &str,
+        aad: &str,
+        plaintext: &str,
+        ciphertext: &str,
+        tag: &str,
+    ) {
+        let cipher = <Aes128 as BlockCipher>::new(&hex_vec(key));
+        let nonce = hex_vec(nonce);
+        let ccm = Ccm::new(&cipher, tag_len, 15 - nonce.len());
+        let aad = hex_vec(aad);
+        let mut buf = hex_vec(plaintext);
+        let mut computed_tag = vec![0; tag_len];
+        ccm.encrypt(&nonce, &aad, &mut buf, &mut computed_tag)
+            .unwrap();
+        assert_eq!(buf, hex_vec(ciphertext));
+        assert_eq!(computed_tag, hex_vec(tag));
+
+        ccm.decrypt(&nonce, &aad, &mut buf, &computed_tag).unwrap();
+        assert_eq!(buf, hex_vec(plaintext));
+    }
+
+    #[test]
+    fn test_rfc3610() {
+        // packet vectors #1 to #3
+        let key = "c0c1c2c3c4c5c6c7c8c9cacbcccdcecf";
+        check_vector(
+            key,
+            8,
+            "00000003020100a0a1a2a3a4a5",
+            "0001020304050607",
+            "08090a0b0c0d0e0f101112131415161718191a1b1c1d1e",
+            "588c979a61c663d2f066d0c2c0f989806d5f6b61dac384",
+            "17e8d12cfdf926e0",
+        );
+        check_vector(
+            key,
+            8,
+            "00000004030201a0a1a2a3a4a5",
+            "0001020304050607",
+            "08090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+            "72c91a36e135f8cf291ca894085c87e3cc15c439c9e43a3b",
+            "a091d56e10400916",
+        );
+        check_vector(
+            key,
+            8,
+            "00000005040302a0a1a2a3a4a5",
+            "0001020304050607",
+            "08090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20",
+            "51b1e5f44a197d1da46b0f8e2d282ae871e838bb64da859657",
+            "4adaa76fbd9fb0c5",
+        );
+    }
+
+    #[test]
+    fn test_sp800_38c() {
+        // examples 1 to 4 of appendix C
+        let key = "404142434445464748494a4b4c4d4e4f";
+        check_vector(
+            key,
+            4,
+            "10111213141516",
+            "0001020304050607",
+            "20212223",
+            "7162015b",
+            "4dac255d",
+        );
+        check_vector(
+            key,
+            6,
+            "1011121314151617",
+            "000102030405060708090a0b0c0d0e0f",
+            "202122232425262728292a2b2c2d2e2f",
+            "d2a1f0e051ea5f62081a7792073d593d",
+            "1fc64fbfaccd",
+        );
+        check_vector(
+            key,
+            8,
+            "101112131415161718191a1b",
+            "000102030405060708090a0b0c0d0e0f10111213",
+            "202122232425262728292a2b2c2d2e2f3031323334353637",
+            "e3b201a9f5b71a7a9b1ceaeccd97e70b6176aad9a4428aa5",
+            "484392fbc1b09951",
+        );
+
+        let aad: Vec<u8> = (0..=255).cycle().take(1 << 16).collect();
+        let mut encoded = vec![0; 2 * aad.len()];
+        check_vector(
+            key,
+            14,
+            "101112131415161718191a1b1c",
+            HEX.encode_to_slice(&aad, &mut encoded).unwrap(),
+            "202122232425262728292a2b2c2d2e2f 303132333435363738393a3b3c3d3e3f",
+            "69915dad1e84c6376a68c2967e4dab61 5ae0fd1faec44cc484828529463ccf72",
+            "b4ac6bec93e8598e7f0dadbcea5b",
+        );
+    }
+
+    #[test]
+    fn test_invalid_tag() {
+        let cipher = <Aes128 as BlockCipher>::new(&[0; 16]);
+        let ccm = Ccm::new(&cipher, 8, 2);
+        let mut buf = *b"secret message";
+        let mut tag = [0; 8];
+        ccm.encrypt(&[1; 13], b"header", &mut buf, &mut tag)
+            .unwrap();
+        let ciphertext = buf;
+
+        tag[7] ^= 1;
+        assert_eq!(
+            ccm.decrypt(&[1; 13], b"header", &mut buf, &tag),
+            Err(ModeError::InvalidTag)
+        );
+        assert_eq!(buf, [0; 14]);
+        tag[7] ^= 1;
+        buf = ciphertext;
+        assert_eq!(
+            ccm.decrypt(&[1; 13], b"headeR", &mut buf, &tag),
+            Err(ModeError::InvalidTag)
+        );
+        buf = ciphertext;
+        ccm.decrypt(&[1; 13], b"header", &mut buf, &tag).unwrap();
+        assert_eq!(&buf, b"secret message");
+    }
+
+    #[test]
+    fn test_invalid_lengths() {
+        let cipher = <Aes128 as BlockCipher>::new(&[0; 16]);
+        let ccm = Ccm::new(&cipher, 16, 2);
+        assert_eq!(ccm.nonce_len(), 13);
+        let mut buf = [0; 16];
+        assert_eq!(
+            ccm.encrypt(&[0; 12], &[], &mut buf, &mut [0; 16]),
+            Err(ModeError::InvalidLength)
+        );
+        assert_eq!(
+            ccm.encrypt(&[0; 13], &[], &mut buf, &mut [0; 8]),
+            Err(ModeError::InvalidLength)
+        );
+        let mut long = vec![0; 1 << 16];
+        assert_eq!(
+            ccm.encrypt(&[0; 13], &[], &mut long, &mut [0; 16]),
+            Err(ModeError::InvalidLength)
+        );
+        assert!(ccm
+            .encrypt(&[0; 13], &[], &mut long[1..], &mut [0; 16])
+            .is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid tag length")]
+    fn test_odd_tag_length() {
+        Ccm::new(&<Aes128 as BlockCipher>::new(&[0; 16]), 5, 2);
+    }
+
+    #[quickcheck]
+    fn test_roundtrip(msg: Vec<u8>, aad: Vec<u8>, tag_len: usize, length_size: usize) {
+        let cipher = <Twofish as BlockCipher>::new(&[3; 32]);
+        let tag_len = 4 + 2 * (tag_len % 7);
+        let length_size = 2 + length_size % 7;
+        let ccm = Ccm::new(&cipher, tag_len, length_size);
+        let nonce = vec![9; ccm.nonce_len()];
+        let mut buf = msg.clone();
+        let mut tag = vec![0; tag_len];
+        ccm.encrypt(&nonce, &aad, &mut buf, &mut tag).unwrap();
+        ccm.decrypt(&nonce, &aad, &mut buf, &tag).unwrap();
+        assert_eq!(buf, msg);
+    }
+}
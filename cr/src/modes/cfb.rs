@@ -0,0 +1,345 @@
+//! Cipher feedback (CFB) mode
+//!
+//! The keystream is obtained by encrypting a shift register, into which the ciphertext is fed
+//! back. [`Cfb`] feeds back whole blocks, [`Cfb8`] single bytes and [`Cfb1`] single bits; the
+//! smaller segments need one block encryption per byte or bit respectively. The IV must be
+//! unpredictable, e.g. random.
+
+use super::{check_block_size, MAX_BLOCK_SIZE};
+use crate::zeroize::Zeroize;
+use crate::BlockCipher;
+
+/// Full-block CFB mode, wiped on drop
+///
+/// The message can have any length and be processed in chunks of any length.
+pub struct Cfb<'a, C: BlockCipher> {
+    cipher: &'a C,
+    /// Previous ciphertext block, with the first `pos` bytes replaced by the current one
+    register: [u8; MAX_BLOCK_SIZE],
+    keystream: [u8; MAX_BLOCK_SIZE],
+    /// Number of used bytes of `keystream`
+    pos: usize,
+}
+
+impl<'a, C: BlockCipher> Cfb<'a, C> {
+    /// Panics if `iv` is not a single block or the block size is larger than 16 bytes.
+    pub fn new(cipher: &'a C, iv: &[u8]) -> Self {
+        Self {
+            cipher,
+            register: register::<C>(iv),
+            keystream: [0; MAX_BLOCK_SIZE],
+            pos: 0,
+        }
+    }
+
+    /// Encrypts `buf` in place
+    pub fn encrypt(&mut self, buf: &mut [u8]) {
+        for b in buf {
+            let keystream = self.next_keystream_byte();
+            *b ^= keystream;
+            self.register[self.pos] = *b;
+            self.pos += 1;
+        }
+    }
+
+    /// Decrypts `buf` in place
+    pub fn decrypt(&mut self, buf: &mut [u8]) {
+        for b in buf {
+            let keystream = self.next_keystream_byte();
+            self.register[self.pos] = *b;
+            *b ^= keystream;
+            self.pos += 1;
+        }
+    }
+
+    fn next_keystream_byte(&mut self) -> u8 {
+        if self.pos == C::BLOCK_SIZE {
+            self.pos = 0;
+        }
+        if self.pos == 0 {
+            self.keystream = self.register;
+            self.cipher
+                .encrypt_block(&mut self.keystream[..C::BLOCK_SIZE]);
+        }
+        self.keystream[self.pos]
+    }
+}
+
+impl<C: BlockCipher> Zeroize for Cfb<'_, C> {
+    fn zeroize(&mut self) {
+        self.register.zeroize();
+        self.keystream.zeroize();
+        self.pos.zeroize();
+    }
+}
+
+impl<C: BlockCipher> Drop for Cfb<'_, C> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+/// 8-bit CFB mode, wiped on drop
+pub struct Cfb8<'a, C: BlockCipher> {
+    cipher: &'a C,
+    register: [u8; MAX_BLOCK_SIZE],
+}
+
+impl<'a, C: BlockCipher> Cfb8<'a, C> {
+    /// Panics if `iv` is not a single block or the block size is larger than 16 bytes.
+    pub fn new(cipher: &'a C, iv: &[u8]) -> Self {
+        Self {
+            cipher,
+            register: register::<C>(iv),
+        }
+    }
+
+    /// Encrypts `buf` in place
+    pub fn encrypt(&mut self, buf: &mut [u8]) {
+        for b in buf {
+            *b ^= self.keystream_byte();
+            self.shift_in(*b);
+        }
+    }
+
+    /// Decrypts `buf` in place
+    pub fn decrypt(&mut self, buf: &mut [u8]) {
+        for b in buf {
+            let keystream = self.keystream_byte();
+            self.shift_in(*b);
+            *b ^= keystream;
+        }
+    }
+
+    fn keystream_byte(&self) -> u8 {
+        let mut block = self.register;
+        self.cipher.encrypt_block(&mut block[..C::BLOCK_SIZE]);
+        let keystream = block[0];
+        block.zeroize();
+        keystream
+    }
+
+    fn shift_in(&mut self, ciphertext: u8) {
+        self.register.copy_within(1..C::BLOCK_SIZE, 0);
+        self.register[C::BLOCK_SIZE - 1] = ciphertext;
+    }
+}
+
+impl<C: BlockCipher> Zeroize for Cfb8<'_, C> {
+    fn zeroize(&mut self) {
+        self.register.zeroize();
+    }
+}
+
+impl<C: BlockCipher> Drop for Cfb8<'_, C> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+/// 1-bit CFB mode, wiped on drop
+///
+/// The bits of each byte are processed from the most to the least significant one.
+pub struct Cfb1<'a, C: BlockCipher> {
+    cipher: &'a C,
+    register: [u8; MAX_BLOCK_SIZE],
+}
+
+impl<'a, C: BlockCipher> Cfb1<'a, C> {
+    /// Panics if `iv` is not a single block or the block size is larger than 16 bytes.
+    pub fn new(cipher: &'a C, iv: &[u8]) -> Self {
+        Self {
+            cipher,
+            register: register::<C>(iv),
+        }
+    }
+
+    /// Encrypts `buf` in place
+    pub fn encrypt(&mut self, buf: &mut [u8]) {
+        for b in buf {
+            for i in (0..8).rev() {
+                let bit = (*b >> i & 1) ^ self.keystream_bit();
+                self.shift_in(bit);
+                *b = *b & !(1 << i) | bit << i;
+            }
+        }
+    }
+
+    /// Decrypts `buf` in place
+    pub fn decrypt(&mut self, buf: &mut [u8]) {
+        for b in buf {
+            for i in (0..8).rev() {
+                let keystream = self.keystream_bit();
+                self.shift_in(*b >> i & 1);
+                *b ^= keystream << i;
+            }
+        }
+    }
+
+    fn keystream_bit(&self) -> u8 {
+        let mut block = self.register;
+        self.cipher.encrypt_block(&mut block[..C::BLOCK_SIZE]);
+        let keystream = block[0] >> 7;
+        block.zeroize();
+        keystream
+    }
+
+    fn shift_in(&mut self, ciphertext: u8) {
+        let register = &mut self.register[..C::BLOCK_SIZE];
+        for i in 0..C::BLOCK_SIZE - 1 {
+            register[i] = register[i] << 1 | register[i + 1] >> 7;
+        }
+        register[C::BLOCK_SIZE - 1] = register[C::BLOCK_SIZE - 1] << 1 | ciphertext;
+    }
+}
+
+impl<C: BlockCipher> Zeroize for Cfb1<'_, C> {
+    fn zeroize(&mut self) {
+        self.register.zeroize();
+    }
+}
+
+impl<C: BlockCipher> Drop for Cfb1<'_, C> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+fn register<C: BlockCipher>(iv: &[u8]) -> [u8; MAX_BLOCK_SIZE] {
+    check_block_size::<C>();
+    assert_eq!(iv.len(), C::BLOCK_SIZE, "invalid IV size");
+    let mut register = [0; MAX_BLOCK_SIZE];
+    register[..C::BLOCK_SIZE].copy_from_slice(iv);
+    register
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes::{Aes128, Aes192, Aes256};
+    use crate::des::Des;
+    use crate::hex_vec;
+    use crate::twofish::Twofish;
+
+    use quickcheck_macros::quickcheck;
+
+    // SP 800-38A, appendix F.3
+    const IV: &str = "000102030405060708090a0b0c0d0e0f";
+    const PLAINTEXT: &str = "6bc1bee22e409f96e93d7e117393172a ae2d8a571e03ac9c9eb76fac45af8e51
+                             30c81c46a35ce411e5fbc1191a0a52ef f69f2445df4f9b17ad2b417be66c3710";
+    const KEY_128: &str = "2b7e151628aed2a6abf7158809cf4f3c";
+    const KEY_192: &str = "8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b";
+    const KEY_256: &str = "603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4";
+
+    macro_rules! check_vector {
+        ($mode:ident, $cipher:ty, $key:expr, $plaintext:expr, $ciphertext:expr) => {{
+            let cipher = <$cipher as BlockCipher>::new(&hex_vec($key));
+            let iv = hex_vec(IV);
+            let mut buf = hex_vec($plaintext);
+            $mode::new(&cipher, &iv).encrypt(&mut buf);
+            assert_eq!(buf, hex_vec($ciphertext));
+            $mode::new(&cipher, &iv).decrypt(&mut buf);
+            assert_eq!(buf, hex_vec($plaintext));
+        }};
+    }
+
+    #[test]
+    fn test_cfb1() {
+        // the vectors encrypt the first 16 bits of the plaintext
+        check_vector!(Cfb1, Aes128, KEY_128, "6bc1", "68b3");
+        check_vector!(Cfb1, Aes192, KEY_192, "6bc1", "9359");
+        check_vector!(Cfb1, Aes256, KEY_256, "6bc1", "9029");
+    }
+
+    #[test]
+    fn test_cfb8() {
+        // the vectors encrypt the first 18 bytes of the plaintext
+        let plaintext = "6bc1bee22e409f96e93d7e117393172aae2d";
+        check_vector!(
+            Cfb8,
+            Aes128,
+            KEY_128,
+            plaintext,
+            "3b79424c9c0dd436bace9e0ed4586a4f32b9"
+        );
+        check_vector!(
+            Cfb8,
+            Aes192,
+            KEY_192,
+            plaintext,
+            "cda2521ef0a905ca44cd057cbf0d47a0678a"
+        );
+        check_vector!(
+            Cfb8,
+            Aes256,
+            KEY_256,
+            plaintext,
+            "dc1f1a8520a64db55fcc8ac554844e889700"
+        );
+    }
+
+    #[test]
+    fn test_cfb128() {
+        check_vector!(
+            Cfb,
+            Aes128,
+            KEY_128,
+            PLAINTEXT,
+            "3b3fd92eb72dad20333449f8e83cfb4a c8a64537a0b3a93fcde3cdad9f1ce58b
+             26751f67a3cbb140b1808cf187a4f4df c04b05357c5d1c0eeac4c66f9ff7f2e6"
+        );
+        check_vector!(
+            Cfb,
+            Aes192,
+            KEY_192,
+            PLAINTEXT,
+            "cdc80d6fddf18cab34c25909c99a4174 67ce7f7f81173621961a2b70171d3d7a
+             2e1e8a1dd59b88b1c8e60fed1efac4c9 c05f9f9ca9834fa042ae8fba584b09ff"
+        );
+        check_vector!(
+            Cfb,
+            Aes256,
+            KEY_256,
+            PLAINTEXT,
+            "dc7e84bfda79164b7ecd8486985d3860 39ffed143b28b1c832113c6331e5407b
+             df10132415e54b92a13ed0a8267ae2f9 75a385741ab9cef82031623d55b1e471"
+        );
+    }
+
+    macro_rules! check_roundtrip {
+        ($mode:ident, $cipher:expr, $iv:expr, $msg:expr, $split:expr) => {{
+            let cipher = $cipher;
+            let mut buf = $msg.clone();
+            let (head, tail) = buf.split_at_mut($split % ($msg.len() + 1));
+            let mut mode = $mode::new(&cipher, &$iv);
+            mode.encrypt(head);
+            mode.encrypt(tail);
+
+            let mut expected = $msg.clone();
+            $mode::new(&cipher, &$iv).encrypt(&mut expected);
+            assert_eq!(buf, expected);
+
+            let (head, tail) = buf.split_at_mut($split % ($msg.len() + 1));
+            let mut mode = $mode::new(&cipher, &$iv);
+            mode.decrypt(head);
+            mode.decrypt(tail);
+            assert_eq!(buf, $msg);
+        }};
+    }
+
+    #[quickcheck]
+    fn test_roundtrip(msg: Vec<u8>, split: usize) {
+        let aes = || <Aes128 as BlockCipher>::new(&[1; 16]);
+        let des = || Des::new([2; 8]);
+        let twofish = || <Twofish as BlockCipher>::new(&[3; 32]);
+        check_roundtrip!(Cfb, aes(), [4; 16], msg, split);
+        check_roundtrip!(Cfb, des(), [5; 8], msg, split);
+        check_roundtrip!(Cfb, twofish(), [6; 16], msg, split);
+        check_roundtrip!(Cfb8, aes(), [4; 16], msg, split);
+        check_roundtrip!(Cfb8, des(), [5; 8], msg, split);
+        check_roundtrip!(Cfb8, twofish(), [6; 16], msg, split);
+        check_roundtrip!(Cfb1, aes(), [4; 16], msg, split);
+        check_roundtrip!(Cfb1, des(), [5; 8], msg, split);
+        check_roundtrip!(Cfb1, twofish(), [6; 16], msg, split);
+    }
+}
@@ -0,0 +1,337 @@
+//! Counter (CTR) mode
+//!
+//! The cipher encrypts successive values of a counter block, and the resulting keystream is
+//! XORed with the message, so encryption and decryption are the same operation. The keystream
+//! of any position can be computed directly, which allows random access with [`Ctr::seek`].
+//!
+//! The counter is the big-endian integer in the last 32, 64 or 128 bits of the counter block;
+//! it wraps around without carrying into the other bytes. A combination of key and initial
+//! counter block must never be reused. The keystream ends after 2^width blocks, before a
+//! counter value would repeat, or at the largest position representable as `u64`.
+
+use super::{check_block_size, ModeError, MAX_BLOCK_SIZE};
+use crate::zeroize::Zeroize;
+use crate::BlockCipher;
+
+use core::convert::TryInto;
+
+/// Width of the counter in the counter block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CounterWidth {
+    Bits32,
+    Bits64,
+    Bits128,
+}
+
+impl CounterWidth {
+    fn bytes(self) -> usize {
+        match self {
+            CounterWidth::Bits32 => 4,
+            CounterWidth::Bits64 => 8,
+            CounterWidth::Bits128 => 16,
+        }
+    }
+}
+
+/// CTR mode keystream, wiped on drop
+pub struct Ctr<'a, C: BlockCipher> {
+    cipher: &'a C,
+    initial_counter: [u8; MAX_BLOCK_SIZE],
+    width: CounterWidth,
+    /// Position in the keystream in bytes
+    offset: u64,
+    /// Keystream block at `keystream_idx`
+    keystream: [u8; MAX_BLOCK_SIZE],
+    keystream_idx: Option<u64>,
+}
+
+impl<'a, C: BlockCipher> Ctr<'a, C> {
+    /// Starts the keystream at `initial_counter`
+    ///
+    /// Panics if `initial_counter` is not a single block, the counter is wider than a block, or
+    /// the block size is larger than 16 bytes.
+    pub fn new(cipher: &'a C, initial_counter: &[u8], width: CounterWidth) -> Self {
+        check_block_size::<C>();
+        assert_eq!(
+            initial_counter.len(),
+            C::BLOCK_SIZE,
+            "invalid counter block size"
+        );
+        assert!(width.bytes() <= C::BLOCK_SIZE, "counter wider than block");
+        let mut counter = [0; MAX_BLOCK_SIZE];
+        counter[..C::BLOCK_SIZE].copy_from_slice(initial_counter);
+        Self {
+            cipher,
+            initial_counter: counter,
+            width,
+            offset: 0,
+            keystream: [0; MAX_BLOCK_SIZE],
+            keystream_idx: None,
+        }
+    }
+
+    /// Current position in the keystream in bytes
+    pub fn position(&self) -> u64 {
+        self.offset
+    }
+
+    /// Moves to position `offset` in the keystream
+    ///
+    /// Fails with [`ModeError::InvalidLength`] if `offset` is beyond the end of the keystream.
+    pub fn seek(&mut self, offset: u64) -> Result<(), ModeError> {
+        if offset > self.keystream_len() {
+            return Err(ModeError::InvalidLength);
+        }
+        self.offset = offset;
+        Ok(())
+    }
+
+    /// Encrypts or decrypts `buf` in place and advances the position
+    ///
+    /// Fails with [`ModeError::InvalidLength`] and leaves `buf` unchanged if the rest of the
+    /// keystream is shorter than `buf`.
+    pub fn apply_keystream(&mut self, buf: &mut [u8]) -> Result<(), ModeError> {
+        if buf.len() as u64 > self.keystream_len() - self.offset {
+            return Err(ModeError::InvalidLength);
+        }
+        let block_size = C::BLOCK_SIZE as u64;
+        let mut buf = buf;
+        while !buf.is_empty() {
+            let block_idx = self.offset / block_size;
+            if self.keystream_idx != Some(block_idx) {
+                self.generate(block_idx);
+            }
+            let start = (self.offset % block_size) as usize;
+            let len = buf.len().min(C::BLOCK_SIZE - start);
+            let (head, tail) = buf.split_at_mut(len);
+            for (b, k) in head.iter_mut().zip(&self.keystream[start..]) {
+                *b ^= k;
+            }
+            self.offset += len as u64;
+            buf = tail;
+        }
+        Ok(())
+    }
+
+    /// Length of the keystream in bytes, i.e. 2^width blocks capped at `u64::MAX`
+    fn keystream_len(&self) -> u64 {
+        let blocks = 1u128.checked_shl(8 * self.width.bytes() as u32);
+        blocks
+            .and_then(|blocks| blocks.checked_mul(C::BLOCK_SIZE as u128))
+            .map_or(u64::MAX, |len| len.min(u64::MAX as u128) as u64)
+    }
+
+    /// Computes the keystream block `idx`, i.e. the encrypted counter block `initial + idx`
+    fn generate(&mut self, idx: u64) {
+        let block = &mut self.keystream[..C::BLOCK_SIZE];
+        block.copy_from_slice(&self.initial_counter[..C::BLOCK_SIZE]);
+        let counter = &mut block[C::BLOCK_SIZE - self.width.bytes()..];
+        match self.width {
+            CounterWidth::Bits32 => {
+                let value = u32::from_be_bytes((&*counter).try_into().unwrap());
+                counter.copy_from_slice(&value.wrapping_add(idx as u32).to_be_bytes());
+            }
+            CounterWidth::Bits64 => {
+                let value = u64::from_be_bytes((&*counter).try_into().unwrap());
+                counter.copy_from_slice(&value.wrapping_add(idx).to_be_bytes());
+            }
+            CounterWidth::Bits128 => {
+                let value = u128::from_be_bytes((&*counter).try_into().unwrap());
+                counter.copy_from_slice(&value.wrapping_add(idx as u128).to_be_bytes());
+            }
+        }
+        self.cipher.encrypt_block(block);
+        self.keystream_idx = Some(idx);
+    }
+}
+
+impl<C: BlockCipher> Zeroize for Ctr<'_, C> {
+    fn zeroize(&mut self) {
+        self.initial_counter.zeroize();
+        self.keystream.zeroize();
+        self.offset.zeroize();
+        self.keystream_idx = None;
+    }
+}
+
+impl<C: BlockCipher> Drop for Ctr<'_, C> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes::{Aes128, Aes192, Aes256};
+    use crate::des::Des;
+    use crate::twofish::Twofish;
+    use crate::{hex, hex_vec};
+
+    use quickcheck_macros::quickcheck;
+
+    // SP 800-38A, appendix F.5
+    const INITIAL_COUNTER: &str = "f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff";
+    const PLAINTEXT: &str = "6bc1bee22e409f96e93d7e117393172a ae2d8a571e03ac9c9eb76fac45af8e51
+                             30c81c46a35ce411e5fbc1191a0a52ef f69f2445df4f9b17ad2b417be66c3710";
+
+    fn check_vector<C: BlockCipher>(key: &str, ciphertext: &str) {
+        let cipher = C::new(&hex_vec(key));
+        let counter = hex_vec(INITIAL_COUNTER);
+        let mut ctr = Ctr::new(&cipher, &counter, CounterWidth::Bits128);
+        let mut buf = hex_vec(PLAINTEXT);
+        ctr.apply_keystream(&mut buf).unwrap();
+        assert_eq!(buf, hex_vec(ciphertext));
+        assert_eq!(ctr.position(), 64);
+
+        ctr.seek(0).unwrap();
+        ctr.apply_keystream(&mut buf).unwrap();
+        assert_eq!(buf, hex_vec(PLAINTEXT));
+    }
+
+    #[test]
+    fn test_ctr_aes128() {
+        check_vector::<Aes128>(
+            "2b7e151628aed2a6abf7158809cf4f3c",
+            "874d6191b620e3261bef6864990db6ce 9806f66b7970fdff8617187bb9fffdff
+             5ae4df3edbd5d35e5b4f09020db03eab 1e031dda2fbe03d1792170a0f3009cee",
+        );
+    }
+
+    #[test]
+    fn test_ctr_aes192() {
+        check_vector::<Aes192>(
+            "8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b",
+            "1abc932417521ca24f2b0459fe7e6e0b 090339ec0aa6faefd5ccc2c6f4ce8e94
+             1e36b26bd1ebc670d1bd1d665620abf7 4f78a7f6d29809585a97daec58c6b050",
+        );
+    }
+
+    #[test]
+    fn test_ctr_aes256() {
+        check_vector::<Aes256>(
+            "603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4",
+            "601ec313775789a5b7a7f504bbf3d228 f443e3ca4d62b59aca84e990cacaf5c5
+             2b0930daa23de94ce87017ba2d84988d dfc9c58db67aada613c2dd08457941a6",
+        );
+    }
+
+    #[test]
+    fn test_counter_wraps() {
+        let cipher = <Aes128 as BlockCipher>::new(&[0; 16]);
+        let counter: [u8; 16] = hex("000000000000000affffffffffffffff").unwrap();
+        for &(width, expected) in &[
+            (CounterWidth::Bits32, "000000000000000affffffff00000000"),
+            (CounterWidth::Bits64, "000000000000000a0000000000000000"),
+            (CounterWidth::Bits128, "000000000000000b0000000000000000"),
+        ] {
+            let mut keystream = [0; 16];
+            let mut ctr = Ctr::new(&cipher, &counter, width);
+            ctr.seek(16).unwrap();
+            ctr.apply_keystream(&mut keystream).unwrap();
+
+            let mut expected: [u8; 16] = hex(expected).unwrap();
+            cipher.encrypt_block(&mut expected);
+            assert_eq!(keystream, expected, "{:?}", width);
+        }
+    }
+
+    #[test]
+    fn test_counter_exhausted() {
+        let cipher = <Aes128 as BlockCipher>::new(&[0; 16]);
+        let counter: [u8; 16] = hex("000000000000000000000000fffffffe").unwrap();
+        let end = 16 << 32;
+        let mut ctr = Ctr::new(&cipher, &counter, CounterWidth::Bits32);
+
+        // the last block uses the counter value just before the initial one
+        ctr.seek(end - 16).unwrap();
+        let mut keystream = [0; 16];
+        ctr.apply_keystream(&mut keystream).unwrap();
+        let mut expected: [u8; 16] = hex("000000000000000000000000fffffffd").unwrap();
+        cipher.encrypt_block(&mut expected);
+        assert_eq!(keystream, expected);
+        assert_eq!(ctr.position(), end);
+
+        let mut buf = [0; 1];
+        assert_eq!(ctr.apply_keystream(&mut buf), Err(ModeError::InvalidLength));
+        assert_eq!(buf, [0]);
+        assert_eq!(ctr.position(), end);
+
+        ctr.seek(end - 8).unwrap();
+        let mut buf = [0; 9];
+        assert_eq!(ctr.apply_keystream(&mut buf), Err(ModeError::InvalidLength));
+        assert_eq!(buf, [0; 9]);
+        assert_eq!(ctr.seek(end + 1), Err(ModeError::InvalidLength));
+        assert_eq!(ctr.position(), end - 8);
+    }
+
+    #[test]
+    fn test_seek_to_end() {
+        let cipher = <Aes128 as BlockCipher>::new(&[0; 16]);
+        let mut ctr = Ctr::new(&cipher, &[0; 16], CounterWidth::Bits128);
+        ctr.seek(u64::MAX - 1).unwrap();
+        let mut buf = [0; 1];
+        ctr.apply_keystream(&mut buf).unwrap();
+        assert_eq!(ctr.position(), u64::MAX);
+        assert_eq!(ctr.apply_keystream(&mut []), Ok(()));
+        assert_eq!(ctr.apply_keystream(&mut buf), Err(ModeError::InvalidLength));
+
+        let cipher = Des::new([0; 8]);
+        let mut ctr = Ctr::new(&cipher, &[0; 8], CounterWidth::Bits64);
+        ctr.seek(u64::MAX).unwrap();
+        assert_eq!(ctr.apply_keystream(&mut buf), Err(ModeError::InvalidLength));
+    }
+
+    fn check_seek<C: BlockCipher>(cipher: &C, counter: &[u8], msg: &[u8], offset: usize) {
+        let offset = offset % (msg.len() + 1);
+        let mut ciphertext = msg.to_vec();
+        Ctr::new(cipher, counter, CounterWidth::Bits32)
+            .apply_keystream(&mut ciphertext)
+            .unwrap();
+
+        let mut ctr = Ctr::new(cipher, counter, CounterWidth::Bits32);
+        ctr.seek(offset as u64).unwrap();
+        let mut tail = ciphertext[offset..].to_vec();
+        ctr.apply_keystream(&mut tail).unwrap();
+        assert_eq!(tail, &msg[offset..]);
+    }
+
+    #[quickcheck]
+    fn test_seek_roundtrip(msg: Vec<u8>, offset: usize) {
+        check_seek(
+            &<Aes128 as BlockCipher>::new(&[1; 16]),
+            &[4; 16],
+            &msg,
+            offset,
+        );
+        check_seek(&Des::new([2; 8]), &[5; 8], &msg, offset);
+        check_seek(
+            &<Twofish as BlockCipher>::new(&[3; 32]),
+            &[6; 16],
+            &msg,
+            offset,
+        );
+    }
+
+    #[quickcheck]
+    fn test_chunked(msg: Vec<u8>, split: usize) {
+        let cipher = Des::new([7; 8]);
+        let mut expected = msg.clone();
+        Ctr::new(&cipher, &[8; 8], CounterWidth::Bits64)
+            .apply_keystream(&mut expected)
+            .unwrap();
+
+        let mut buf = msg.clone();
+        let (head, tail) = buf.split_at_mut(split % (msg.len() + 1));
+        let mut ctr = Ctr::new(&cipher, &[8; 8], CounterWidth::Bits64);
+        ctr.apply_keystream(head).unwrap();
+        ctr.apply_keystream(tail).unwrap();
+        assert_eq!(buf, expected);
+    }
+
+    #[test]
+    #[should_panic(expected = "counter wider than block")]
+    fn test_counter_too_wide() {
+        Ctr::new(&Des::new([0; 8]), &[0; 8], CounterWidth::Bits128);
+    }
+}
@@ -0,0 +1,137 @@
+//! Electronic codebook (ECB) mode
+//!
+//! Each block is encrypted independently, so equal plaintext blocks give equal ciphertext
+//! blocks. Use ECB only for interoperability; prefer CBC or another mode with an IV.
+
+use super::{check_blocks, pkcs7_pad, unpad_decrypted, ModeError};
+use crate::BlockCipher;
+
+/// Encrypts `buf` in place, which must be a whole number of blocks
+pub fn encrypt<C: BlockCipher>(cipher: &C, buf: &mut [u8]) -> Result<(), ModeError> {
+    check_blocks::<C>(buf)?;
+    for block in buf.chunks_exact_mut(C::BLOCK_SIZE) {
+        cipher.encrypt_block(block);
+    }
+    Ok(())
+}
+
+/// Decrypts `buf` in place, which must be a whole number of blocks
+pub fn decrypt<C: BlockCipher>(cipher: &C, buf: &mut [u8]) -> Result<(), ModeError> {
+    check_blocks::<C>(buf)?;
+    for block in buf.chunks_exact_mut(C::BLOCK_SIZE) {
+        cipher.decrypt_block(block);
+    }
+    Ok(())
+}
+
+/// Pads the message `buf[..len]` with PKCS#7 and encrypts it in place
+///
+/// `buf` must hold at least [`super::pkcs7_padded_len`] bytes. Returns the ciphertext.
+pub fn encrypt_padded<'a, C: BlockCipher>(
+    cipher: &C,
+    buf: &'a mut [u8],
+    len: usize,
+) -> Result<&'a [u8], ModeError> {
+    let buf = pkcs7_pad(buf, len, C::BLOCK_SIZE)?;
+    encrypt(cipher, buf)?;
+    Ok(buf)
+}
+
+/// Decrypts `buf` in place and removes the PKCS#7 padding; returns the plaintext
+///
+/// `buf` is wiped if the padding is invalid.
+pub fn decrypt_padded<'a, C: BlockCipher>(
+    cipher: &C,
+    buf: &'a mut [u8],
+) -> Result<&'a [u8], ModeError> {
+    decrypt(cipher, buf)?;
+    unpad_decrypted(buf, C::BLOCK_SIZE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes::{Aes128, Aes256};
+    use crate::des::Des;
+    use crate::twofish::Twofish;
+    use crate::{hex, hex_vec};
+
+    use quickcheck_macros::quickcheck;
+
+    // SP 800-38A, appendix F.1
+    const PLAINTEXT: &str = "6bc1bee22e409f96e93d7e117393172a ae2d8a571e03ac9c9eb76fac45af8e51
+                             30c81c46a35ce411e5fbc1191a0a52ef f69f2445df4f9b17ad2b417be66c3710";
+
+    #[test]
+    fn test_ecb_aes128() {
+        let cipher = Aes128::new(hex("2b7e151628aed2a6abf7158809cf4f3c").unwrap());
+        let mut buf = hex_vec(PLAINTEXT);
+        encrypt(&cipher, &mut buf).unwrap();
+        assert_eq!(
+            buf,
+            hex_vec(
+                "3ad77bb40d7a3660a89ecaf32466ef97 f5d3d58503b9699de785895a96fdbaaf
+                 43b1cd7f598ece23881b00e3ed030688 7b0c785e27e8ad3f8223207104725dd4"
+            )
+        );
+        decrypt(&cipher, &mut buf).unwrap();
+        assert_eq!(buf, hex_vec(PLAINTEXT));
+    }
+
+    #[test]
+    fn test_ecb_aes256() {
+        let cipher = Aes256::new(
+            hex("603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4").unwrap(),
+        );
+        let mut buf = hex_vec(PLAINTEXT);
+        encrypt(&cipher, &mut buf).unwrap();
+        assert_eq!(
+            buf,
+            hex_vec(
+                "f3eed1bdb5d2a03c064b5a7e3db181f8 591ccb10d410ed26dc5ba74a31362870
+                 b6ed21b99ca6f4f9f153e7b1beafed1d 23304b7a39f9f3ff067d8d8f9e24ecc7"
+            )
+        );
+    }
+
+    #[test]
+    fn test_invalid_length() {
+        let cipher = Des::new([0; 8]);
+        assert_eq!(
+            encrypt(&cipher, &mut [0; 12]),
+            Err(ModeError::InvalidLength)
+        );
+        assert_eq!(
+            decrypt_padded(&cipher, &mut []),
+            Err(ModeError::InvalidLength)
+        );
+    }
+
+    #[test]
+    fn test_invalid_padding() {
+        let cipher = Des::new([0; 8]);
+        let mut buf = [0; 16];
+        encrypt_padded(&cipher, &mut buf, 12).unwrap();
+        buf[15] ^= 1;
+        assert_eq!(
+            decrypt_padded(&cipher, &mut buf),
+            Err(ModeError::InvalidPadding)
+        );
+        assert_eq!(buf, [0; 16]);
+    }
+
+    fn roundtrip<C: BlockCipher>(cipher: &C, msg: &[u8]) {
+        let mut buf = msg.to_vec();
+        buf.resize(super::super::pkcs7_padded_len(msg.len(), C::BLOCK_SIZE), 0);
+        let ciphertext = encrypt_padded(cipher, &mut buf, msg.len()).unwrap();
+        assert_eq!(ciphertext.len() % C::BLOCK_SIZE, 0);
+        assert_eq!(decrypt_padded(cipher, &mut buf).unwrap(), msg);
+    }
+
+    #[quickcheck]
+    fn test_padded_roundtrip(msg: Vec<u8>) {
+        roundtrip(&<Aes128 as BlockCipher>::new(&[1; 16]), &msg);
+        roundtrip(&Des::new([2; 8]), &msg);
+        roundtrip(&<Twofish as BlockCipher>::new(&[3; 32]), &msg);
+    }
+}
@@ -0,0 +1,552 @@
+//! Galois/Counter Mode (GCM) authenticated encryption
+//!
+//! https://nvlpubs.nist.gov/nistpubs/Legacy/SP/nistspecialpublication800-38d.pdf
+//!
+//! The message is encrypted in CTR mode, and the associated data and the ciphertext are
+//! authenticated with [`Ghash`]. GCM requires a cipher with 128-bit blocks, e.g. AES. A nonce
+//! must never be reused with the same key; 96-bit nonces are recommended.
+
+use super::ctr::{CounterWidth, Ctr};
+use super::ModeError;
+use crate::ct::ConstantTimeEq;
+use crate::zeroize::Zeroize;
+use crate::BlockCipher;
+
+use core::convert::TryInto;
+
+/// GCM authenticated encryption, wiped on drop
+pub struct Gcm<'a, C: BlockCipher> {
+    cipher: &'a C,
+    /// Hash key, the encrypted zero block
+    h: [u8; 16],
+}
+
+impl<'a, C: BlockCipher> Gcm<'a, C> {
+    /// Panics if the block size of the cipher is not 128 bits.
+    pub fn new(cipher: &'a C) -> Self {
+        assert_eq!(C::BLOCK_SIZE, 16, "GCM requires 128-bit blocks");
+        let mut h = [0; 16];
+        cipher.encrypt_block(&mut h);
+        Self { cipher, h }
+    }
+
+    /// Encrypts `buf` in place and writes the authentication tag to `tag`
+    ///
+    /// The tag can be truncated to 12 to 16 bytes, or to 4 or 8 bytes for applications which
+    /// limit the message length accordingly (see SP 800-38D, appendix C). The nonce must not be
+    /// empty, and the message must not be longer than 2^32 - 2 blocks.
+    pub fn encrypt(
+        &self,
+        nonce: &[u8],
+        aad: &[u8],
+        buf: &mut [u8],
+        tag: &mut [u8],
+    ) -> Result<(), ModeError> {
+        check_lengths(nonce.len(), aad.len(), buf.len(), tag.len())?;
+        let mut j0 = self.initial_counter(nonce);
+        self.ctr(&j0, buf);
+        let mut full_tag = self.tag(&mut j0, aad, buf);
+        tag.copy_from_slice(&full_tag[..tag.len()]);
+        full_tag.zeroize();
+        j0.zeroize();
+        Ok(())
+    }
+
+    /// Verifies `tag` and decrypts `buf` in place
+    ///
+    /// The tag is compared in constant time. If it does not match, `buf` is left encrypted.
+    pub fn decrypt(
+        &self,
+        nonce: &[u8],
+        aad: &[u8],
+        buf: &mut [u8],
+        tag: &[u8],
+    ) -> Result<(), ModeError> {
+        check_lengths(nonce.len(), aad.len(), buf.len(), tag.len())?;
+        let mut j0 = self.initial_counter(nonce);
+        let mut tag_mask = j0;
+        let mut expected = self.tag(&mut tag_mask, aad, buf);
+        tag_mask.zeroize();
+        let valid = bool::from(expected[..tag.len()].ct_eq(tag));
+        expected.zeroize();
+        if !valid {
+            j0.zeroize();
+            return Err(ModeError::InvalidTag);
+        }
+        self.ctr(&j0, buf);
+        j0.zeroize();
+        Ok(())
+    }
+
+    /// Computes the pre-counter block J0 from the nonce
+    fn initial_counter(&self, nonce: &[u8]) -> [u8; 16] {
+        let mut j0 = [0; 16];
+        if nonce.len() == 12 {
+            j0[..12].copy_from_slice(nonce);
+            j0[15] = 1;
+        } else {
+            let mut ghash = Ghash::new(&self.h);
+            ghash.update_padded(nonce);
+            ghash.update_lengths(0, nonce.len() as u64);
+            j0 = ghash.finalize();
+        }
+        j0
+    }
+
+    /// Applies the keystream starting at the counter block J0 + 1
+    fn ctr(&self, j0: &[u8; 16], buf: &mut [u8]) {
+        let mut ctr = Ctr::new(self.cipher, j0, CounterWidth::Bits32);
+        ctr.seek(16).expect("keystream is longer than a block");
+        ctr.apply_keystream(buf).expect("message length is checked");
+    }
+
+    /// Computes the full tag; `j0` is encrypted in place
+    fn tag(&self, j0: &mut [u8; 16], aad: &[u8], ciphertext: &[u8]) -> [u8; 16] {
+        let mut ghash = Ghash::new(&self.h);
+        ghash.update_padded(aad);
+        ghash.update_padded(ciphertext);
+        ghash.update_lengths(aad.len() as u64, ciphertext.len() as u64);
+        let mut tag = ghash.finalize();
+        self.cipher.encrypt_block(j0);
+        super::xor(&mut tag, j0);
+        tag
+    }
+}
+
+fn check_lengths(
+    nonce_len: usize,
+    aad_len: usize,
+    msg_len: usize,
+    tag_len: usize,
+) -> Result<(), ModeError> {
+    let valid_tag_len = matches!(tag_len, 4 | 8 | 12..=16);
+    // at most 2^32 - 2 blocks, so that the counter does not wrap around to J0, and the lengths
+    // of the nonce and the associated data in bits must fit into 64 bits
+    let valid_len = msg_len as u64 <= ((1 << 32) - 2) * 16
+        && (nonce_len as u64) < 1 << 61
+        && (aad_len as u64) < 1 << 61;
+    if nonce_len == 0 || !valid_tag_len || !valid_len {
+        return Err(ModeError::InvalidLength);
+    }
+    Ok(())
+}
+
+impl<C: BlockCipher> Zeroize for Gcm<'_, C> {
+    fn zeroize(&mut self) {
+        self.h.zeroize();
+    }
+}
+
+impl<C: BlockCipher> Drop for Gcm<'_, C> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+/// GHASH universal hash function of GCM, wiped on drop
+///
+/// The multiplication in GF(2^128) uses no table lookups and no branches on secret data.
+pub struct Ghash {
+    h: u128,
+    y: u128,
+}
+
+impl Ghash {
+    /// Creates the hash with the hash key `h`
+    pub fn new(h: &[u8; 16]) -> Self {
+        Self {
+            h: u128::from_be_bytes(*h),
+            y: 0,
+        }
+    }
+
+    /// Absorbs `data`, padded with zeros to whole blocks
+    pub fn update_padded(&mut self, data: &[u8]) {
+        let mut chunks = data.chunks_exact(16);
+        for block in &mut chunks {
+            self.update_block(block.try_into().unwrap());
+        }
+        let remainder = chunks.remainder();
+        if !remainder.is_empty() {
+            let mut block = [0; 16];
+            block[..remainder.len()].copy_from_slice(remainder);
+            self.update_block(&block);
+            block.zeroize();
+        }
+    }
+
+    /// Absorbs the length block of `aad_len` and `ciphertext_len` bytes
+    pub fn update_lengths(&mut self, aad_len: u64, ciphertext_len: u64) {
+        let lengths = (((aad_len * 8) as u128) << 64) | (ciphertext_len * 8) as u128;
+        self.update_block(&lengths.to_be_bytes());
+    }
+
+    /// Absorbs a single block
+    pub fn update_block(&mut self, block: &[u8; 16]) {
+        self.y = gf_mul(self.y ^ u128::from_be_bytes(*block), self.h);
+    }
+
+    /// Returns the hash and wipes the state
+    pub fn finalize(mut self) -> [u8; 16] {
+        let y = self.y.to_be_bytes();
+        self.zeroize();
+        y
+    }
+}
+
+impl Zeroize for Ghash {
+    fn zeroize(&mut self) {
+        self.h.zeroize();
+        self.y.zeroize();
+    }
+}
+
+impl Drop for Ghash {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+/// Multiplies `x` and `y` in GF(2^128) with the bit order of GCM
+fn gf_mul(x: u128, y: u128) -> u128 {
+    const R: u128 = 0xe1 << 120;
+    let mut z = 0;
+    let mut v = y;
+    for i in (0..128).rev() {
+        let bit = (x >> i) & 1;
+        z ^= v & bit.wrapping_neg();
+        v = (v >> 1) ^ (R & (v & 1).wrapping_neg());
+    }
+    z
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes::{Aes128, Aes192, Aes256};
+    use crate::twofish::Twofish;
+    use crate::{hex, hex_vec};
+
+    use quickcheck_macros::quickcheck;
+
+    const PLAINTEXT: &str = "d9313225f88406e5a55909c5aff5269a 86a7a9531534f7da2e4c303d8a318a72
+                             1c3c0c95956809532fcf0e2449a6b525 b16aedf5aa0de657ba637b391aafd255";
+    const AAD: &str = "feedfacedeadbeeffeedfacedeadbeefabaddad2";
+    const NONCE: &str = "cafebabefacedbaddecaf888";
+
+    fn check_vector<C: BlockCipher>(
+        key: &str,
+        nonce: &str,
+        aad: &str,
+        plaintext: &str,
+        ciphertext: &str,
+        tag: &str,
+    ) {
+        let cipher = C::new(&hex_vec(key));
+        let gcm = Gcm::new(&cipher);
+        let nonce = hex_vec(nonce);
+        let aad = hex_vec(aad);
+        let mut buf = hex_vec(plaintext);
+        let tag = hex_vec(tag);
+        let mut computed_tag = vec![0; tag.len()];
+        gcm.encrypt(&nonce, &aad, &mut buf, &mut computed_tag)
+            .unwrap();
+        assert_eq!(buf, hex_vec(ciphertext));
+        assert_eq!(computed_tag, tag);
+
+        gcm.decrypt(&nonce, &aad, &mut buf, &computed_tag).unwrap();
+        assert_eq!(buf, hex_vec(plaintext));
+    }
+
+    // The GCM test cases of McGrew and Viega, "The Galois/Counter Mode of Operation (GCM)"
+
+    #[test]
+    fn test_gcm_aes128() {
+        let key = "feffe9928665731c6d6a8f9467308308";
+        check_vector::<Aes128>(
+            "00000000000000000000000000000000",
+            "000000000000000000000000",
+            "",
+            "",
+            "",
+            "58e2fccefa7e3061367f1d57a4e7455a",
+        );
+        check_vector::<Aes128>(
+            "00000000000000000000000000000000",
+            "000000000000000000000000",
+            "",
+            "00000000000000000000000000000000",
+            "0388dace60b6a392f328c2b971b2fe78",
+            "ab6e47d42cec13bdf53a67b21257bddf",
+        );
+        check_vector::<Aes128>(
+            key,
+            NONCE,
+            "",
+            PLAINTEXT,
+            "42831ec2217774244b7221b784d0d49c e3aa212f2c02a4e035c17e2329aca12e
+             21d514b25466931c7d8f6a5aac84aa05 1ba30b396a0aac973d58e091473f5985",
+            "4d5c2af327cd64a62cf35abd2ba6fab4",
+        );
+        check_vector::<Aes128>(
+            key,
+            NONCE,
+            AAD,
+            &PLAINTEXT[..PLAINTEXT.len() - 8],
+            "42831ec2217774244b7221b784d0d49c e3aa212f2c02a4e035c17e2329aca12e
+             21d514b25466931c7d8f6a5aac84aa05 1ba30b396a0aac973d58e091",
+            "5bc94fbc3221a5db94fae95ae7121a47",
+        );
+        // 64-bit nonce
+        check_vector::<Aes128>(
+            key,
+            "cafebabefacedbad",
+            AAD,
+            &PLAINTEXT[..PLAINTEXT.len() - 8],
+            "61353b4c2806934a777ff51fa22a4755 699b2a714fcdc6f83766e5f97b6c7423
+             73806900e49f24b22b097544d4896b42 4989b5e1ebac0f07c23f4598",
+            "3612d2e79e3b0785561be14aaca2fccb",
+        );
+        // 480-bit nonce
+        check_vector::<Aes128>(
+            key,
+            "9313225df88406e555909c5aff5269aa 6a7a9538534f7da1e4c303d2a318a728
+             c3c0c95156809539fcf0e2429a6b5254 16aedbf5a0de6a57a637b39b",
+            AAD,
+            &PLAINTEXT[..PLAINTEXT.len() - 8],
+            "8ce24998625615b603a033aca13fb894 be9112a5c3a211a8ba262a3cca7e2ca7
+             01e4a9a4fba43c90ccdcb281d48c7c6f d62875d2aca417034c34aee5",
+            "619cc5aefffe0bfa462af43c1699d050",
+        );
+    }
+
+    #[test]
+    fn test_gcm_aes192() {
+        check_vector::<Aes192>(
+            "000000000000000000000000000000000000000000000000",
+            "000000000000000000000000",
+            "",
+            "",
+            "",
+            "cd33b28ac773f74ba00ed1f312572435",
+        );
+        check_vector::<Aes192>(
+            "000000000000000000000000000000000000000000000000",
+            "000000000000000000000000",
+            "",
+            "00000000000000000000000000000000",
+            "98e7247c07f0fe411c267e4384b0f600",
+            "2ff58d80033927ab8ef4d4587514f0fb",
+        );
+        check_vector::<Aes192>(
+            "feffe9928665731c6d6a8f9467308308feffe9928665731c",
+            NONCE,
+            "",
+            PLAINTEXT,
+            "3980ca0b3c00e841eb06fac4872a2757 859e1ceaa6efd984628593b40ca1e19c
+             7d773d00c144c525ac619d18c84a3f47 18e2448b2fe324d9ccda2710acade256",
+            "9924a7c8587336bfb118024db8674a14",
+        );
+    }
+
+    #[test]
+    fn test_gcm_aes256() {
+        let key = "feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308";
+        check_vector::<Aes256>(
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "000000000000000000000000",
+            "",
+            "",
+            "",
+            "530f8afbc74536b9a963b4f1c4cb738b",
+        );
+        check_vector::<Aes256>(
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "000000000000000000000000",
+            "",
+            "00000000000000000000000000000000",
+            "cea7403d4d606b6e074ec5d3baf39d18",
+            "d0d1c8a799996bf0265b98b5d48ab919",
+        );
+        check_vector::<Aes256>(
+            key,
+            NONCE,
+            "",
+            PLAINTEXT,
+            "522dc1f099567d07f47f37a32a84427d 643a8cdcbfe5c0c97598a2bd2555d1aa
+             8cb08e48590dbb3da7b08b1056828838 c5f61e6393ba7a0abcc9f662898015ad",
+            "b094dac5d93471bdec1a502270e3cc6c",
+        );
+        check_vector::<Aes256>(
+            key,
+            NONCE,
+            AAD,
+            &PLAINTEXT[..PLAINTEXT.len() - 8],
+            "522dc1f099567d07f47f37a32a84427d 643a8cdcbfe5c0c97598a2bd2555d1aa
+             8cb08e48590dbb3da7b08b1056828838 c5f61e6393ba7a0abcc9f662",
+            "76fc6ece0f4e1768cddf8853bb2d551b",
+        );
+    }
+
+    // NIST CAVP, gcmEncryptExtIV128.rsp and gcmEncryptExtIV256.rsp
+
+    #[test]
+    fn test_gcm_cavp() {
+        check_vector::<Aes128>(
+            "11754cd72aec309bf52f7687212e8957",
+            "3c819d9a9bed087615030b65",
+            "",
+            "",
+            "",
+            "250327c674aaf477aef2675748cf6971",
+        );
+        check_vector::<Aes128>(
+            "77be63708971c4e240d1cb79e8d77feb",
+            "e0e00f19fed7ba0136a797f3",
+            "7a43ec1d9c0a5a78a0b16533a6213cab",
+            "",
+            "",
+            "209fcc8d3675ed938e9c7166709dd946",
+        );
+        check_vector::<Aes128>(
+            "7fddb57453c241d03efbed3ac44e371c",
+            "ee283a3fc75575e33efd4887",
+            "",
+            "d5de42b461646c255c87bd2962d3b9a2",
+            "2ccda4a5415cb91e135c2a0f78c9b2fd",
+            "b36d1df9b9d5e596f83e8b7f52971cb3",
+        );
+        check_vector::<Aes256>(
+            "b52c505a37d78eda5dd34f20c22540ea1b58963cf8e5bf8ffa85f9f2492505b4",
+            "516c33929df5a3284ff463d7",
+            "",
+            "",
+            "",
+            "bdc1ac884d332457a1d2664f168c76f0",
+        );
+        // 8-bit nonce
+        check_vector::<Aes128>(
+            "1672c3537afa82004c6b8a46f6f0d026",
+            "05",
+            "",
+            "",
+            "",
+            "8e2ad721f9455f74d8b53d3141f27e8e",
+        );
+        // 120, 112, 104 and 96-bit tags
+        check_vector::<Aes128>(
+            "272f16edb81a7abbea887357a58c1917",
+            "794ec588176c703d3d2a7a07",
+            "",
+            "",
+            "",
+            "b6e6f197168f5049aeda32dafbdaeb",
+        );
+        check_vector::<Aes128>(
+            "81b6844aab6a568c4556a2eb7eae752f",
+            "ce600f59618315a6829bef4d",
+            "",
+            "",
+            "",
+            "89b43e9dbc1b4f597dbbc7655bb5",
+        );
+        check_vector::<Aes128>(
+            "cde2f9a9b1a004165ef9dc981f18651b",
+            "29512c29566c7322e1e33e8e",
+            "",
+            "",
+            "",
+            "2e58ce7dabd107c82759c66a75",
+        );
+        check_vector::<Aes128>(
+            "b01e45cc3088aaba9fa43d81d481823f",
+            "5a2c4a66468713456a4bd5e1",
+            "",
+            "",
+            "",
+            "014280f944f53c681164b2ff",
+        );
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn test_length_limits() {
+        let max_msg_len = ((1 << 32) - 2) * 16;
+        assert_eq!(check_lengths(12, 0, max_msg_len, 16), Ok(()));
+        assert_eq!(
+            check_lengths(12, 0, max_msg_len + 1, 16),
+            Err(ModeError::InvalidLength)
+        );
+        assert_eq!(check_lengths(12, (1 << 61) - 1, 0, 16), Ok(()));
+        assert_eq!(
+            check_lengths(12, 1 << 61, 0, 16),
+            Err(ModeError::InvalidLength)
+        );
+        assert_eq!(check_lengths((1 << 61) - 1, 0, 0, 16), Ok(()));
+        assert_eq!(
+            check_lengths(1 << 61, 0, 0, 16),
+            Err(ModeError::InvalidLength)
+        );
+    }
+
+    #[test]
+    fn test_truncated_tag() {
+        let cipher = <Aes128 as BlockCipher>::new(&[0; 16]);
+        let gcm = Gcm::new(&cipher);
+        let mut buf = [0; 16];
+        let mut tag = [0; 12];
+        gcm.encrypt(&[0; 12], &[], &mut buf, &mut tag).unwrap();
+        assert_eq!(tag, hex::<12>("ab6e47d42cec13bdf53a67b2").unwrap());
+        gcm.decrypt(&[0; 12], &[], &mut buf, &tag).unwrap();
+        assert_eq!(buf, [0; 16]);
+
+        assert_eq!(
+            gcm.encrypt(&[0; 12], &[], &mut buf, &mut [0; 11]),
+            Err(ModeError::InvalidLength)
+        );
+        assert_eq!(
+            gcm.encrypt(&[], &[], &mut buf, &mut [0; 16]),
+            Err(ModeError::InvalidLength)
+        );
+    }
+
+    #[test]
+    fn test_invalid_tag() {
+        let cipher = <Aes128 as BlockCipher>::new(&[0; 16]);
+        let gcm = Gcm::new(&cipher);
+        let mut buf = [0; 20];
+        let mut tag = [0; 16];
+        gcm.encrypt(&[1; 12], b"header", &mut buf, &mut tag)
+            .unwrap();
+        let ciphertext = buf;
+
+        tag[15] ^= 1;
+        assert_eq!(
+            gcm.decrypt(&[1; 12], b"header", &mut buf, &tag),
+            Err(ModeError::InvalidTag)
+        );
+        assert_eq!(buf, ciphertext);
+        tag[15] ^= 1;
+        assert_eq!(
+            gcm.decrypt(&[1; 12], b"headeR", &mut buf, &tag),
+            Err(ModeError::InvalidTag)
+        );
+        buf[0] ^= 1;
+        assert_eq!(
+            gcm.decrypt(&[1; 12], b"header", &mut buf, &tag),
+            Err(ModeError::InvalidTag)
+        );
+    }
+
+    #[quickcheck]
+    fn test_roundtrip(msg: Vec<u8>, aad: Vec<u8>, nonce: Vec<u8>) {
+        let cipher = <Twofish as BlockCipher>::new(&[3; 32]);
+        let gcm = Gcm::new(&cipher);
+        let nonce = if nonce.is_empty() { vec![0] } else { nonce };
+        let mut buf = msg.clone();
+        let mut tag = [0; 16];
+        gcm.encrypt(&nonce, &aad, &mut buf, &mut tag).unwrap();
+        gcm.decrypt(&nonce, &aad, &mut buf, &tag).unwrap();
+        assert_eq!(buf, msg);
+    }
+}
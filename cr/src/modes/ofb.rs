@@ -0,0 +1,144 @@
+//! Output feedback (OFB) mode
+//!
+//! The keystream is obtained by repeatedly encrypting the IV, and XORed with the message, so
+//! encryption and decryption are the same operation. The IV must be unique for each message
+//! encrypted with the same key.
+
+use super::{check_block_size, MAX_BLOCK_SIZE};
+use crate::zeroize::Zeroize;
+use crate::BlockCipher;
+
+/// OFB mode keystream, wiped on drop
+pub struct Ofb<'a, C: BlockCipher> {
+    cipher: &'a C,
+    /// Last output block of the cipher
+    keystream: [u8; MAX_BLOCK_SIZE],
+    /// Number of used bytes of `keystream`
+    pos: usize,
+}
+
+impl<'a, C: BlockCipher> Ofb<'a, C> {
+    /// Panics if `iv` is not a single block or the block size is larger than 16 bytes.
+    pub fn new(cipher: &'a C, iv: &[u8]) -> Self {
+        check_block_size::<C>();
+        assert_eq!(iv.len(), C::BLOCK_SIZE, "invalid IV size");
+        let mut keystream = [0; MAX_BLOCK_SIZE];
+        keystream[..C::BLOCK_SIZE].copy_from_slice(iv);
+        Self {
+            cipher,
+            keystream,
+            pos: C::BLOCK_SIZE,
+        }
+    }
+
+    /// Encrypts or decrypts `buf` in place
+    pub fn apply_keystream(&mut self, buf: &mut [u8]) {
+        for b in buf {
+            if self.pos == C::BLOCK_SIZE {
+                self.cipher
+                    .encrypt_block(&mut self.keystream[..C::BLOCK_SIZE]);
+                self.pos = 0;
+            }
+            *b ^= self.keystream[self.pos];
+            self.pos += 1;
+        }
+    }
+}
+
+impl<C: BlockCipher> Zeroize for Ofb<'_, C> {
+    fn zeroize(&mut self) {
+        self.keystream.zeroize();
+        self.pos.zeroize();
+    }
+}
+
+impl<C: BlockCipher> Drop for Ofb<'_, C> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes::{Aes128, Aes192, Aes256};
+    use crate::des::Des;
+    use crate::hex_vec;
+    use crate::twofish::Twofish;
+
+    use quickcheck_macros::quickcheck;
+
+    // SP 800-38A, appendix F.4
+    const IV: &str = "000102030405060708090a0b0c0d0e0f";
+    const PLAINTEXT: &str = "6bc1bee22e409f96e93d7e117393172a ae2d8a571e03ac9c9eb76fac45af8e51
+                             30c81c46a35ce411e5fbc1191a0a52ef f69f2445df4f9b17ad2b417be66c3710";
+
+    fn check_vector<C: BlockCipher>(key: &str, ciphertext: &str) {
+        let cipher = C::new(&hex_vec(key));
+        let iv = hex_vec(IV);
+        let mut buf = hex_vec(PLAINTEXT);
+        Ofb::new(&cipher, &iv).apply_keystream(&mut buf);
+        assert_eq!(buf, hex_vec(ciphertext));
+        Ofb::new(&cipher, &iv).apply_keystream(&mut buf);
+        assert_eq!(buf, hex_vec(PLAINTEXT));
+    }
+
+    #[test]
+    fn test_ofb_aes128() {
+        check_vector::<Aes128>(
+            "2b7e151628aed2a6abf7158809cf4f3c",
+            "3b3fd92eb72dad20333449f8e83cfb4a 7789508d16918f03f53c52dac54ed825
+             9740051e9c5fecf64344f7a82260edcc 304c6528f659c77866a510d9c1d6ae5e",
+        );
+    }
+
+    #[test]
+    fn test_ofb_aes192() {
+        check_vector::<Aes192>(
+            "8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b",
+            "cdc80d6fddf18cab34c25909c99a4174 fcc28b8d4c63837c09e81700c1100401
+             8d9a9aeac0f6596f559c6d4daf59a5f2 6d9f200857ca6c3e9cac524bd9acc92a",
+        );
+    }
+
+    #[test]
+    fn test_ofb_aes256() {
+        check_vector::<Aes256>(
+            "603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4",
+            "dc7e84bfda79164b7ecd8486985d3860 4febdc6740d20b3ac88f6ad82a4fb08d
+             71ab47a086e86eedf39d1c5bba97c408 0126141d67f37be8538f5a8be740e484",
+        );
+    }
+
+    fn check_chunked<C: BlockCipher>(cipher: &C, iv: &[u8], msg: &[u8], split: usize) {
+        let mut expected = msg.to_vec();
+        Ofb::new(cipher, iv).apply_keystream(&mut expected);
+
+        let mut buf = msg.to_vec();
+        let (head, tail) = buf.split_at_mut(split % (msg.len() + 1));
+        let mut ofb = Ofb::new(cipher, iv);
+        ofb.apply_keystream(head);
+        ofb.apply_keystream(tail);
+        assert_eq!(buf, expected);
+
+        Ofb::new(cipher, iv).apply_keystream(&mut buf);
+        assert_eq!(buf, msg);
+    }
+
+    #[quickcheck]
+    fn test_roundtrip(msg: Vec<u8>, split: usize) {
+        check_chunked(
+            &<Aes128 as BlockCipher>::new(&[1; 16]),
+            &[4; 16],
+            &msg,
+            split,
+        );
+        check_chunked(&Des::new([2; 8]), &[5; 8], &msg, split);
+        check_chunked(
+            &<Twofish as BlockCipher>::new(&[3; 32]),
+            &[6; 16],
+            &msg,
+            split,
+        );
+    }
+}
@@ -0,0 +1,493 @@
+//! XEX-based tweaked-codebook mode with ciphertext stealing (XTS)
+//!
+//! https://ieeexplore.ieee.org/document/4493450 (IEEE 1619)
+//!
+//! XTS encrypts the data units of a storage device, e.g. disk sectors, in place and without
+//! expansion. Each block is encrypted with a tweak derived from the data unit number and the
+//! position of the block, so equal plaintext encrypts differently in different places. A data
+//! unit which is not a whole number of blocks is handled with ciphertext stealing. XTS requires
+//! a cipher with 128-bit blocks, e.g. AES or Twofish, and two independent keys: one for the data
+//! and one for the tweak.
+
+use super::ModeError;
+use crate::ct::ConstantTimeEq;
+use crate::zeroize::Zeroize;
+use crate::BlockCipher;
+
+/// XTS mode
+pub struct Xts<'a, C: BlockCipher> {
+    cipher: &'a C,
+    tweak_cipher: &'a C,
+}
+
+impl<'a, C: BlockCipher> Xts<'a, C> {
+    /// Creates XTS with the data cipher `cipher` and the tweak cipher `tweak_cipher`
+    ///
+    /// The caller is responsible for keying the ciphers with different keys, as required by
+    /// IEEE 1619-2018; use [`Xts::new_strict`] to have this checked. Panics if the block size of
+    /// the cipher is not 128 bits.
+    pub fn new(cipher: &'a C, tweak_cipher: &'a C) -> Self {
+        assert_eq!(C::BLOCK_SIZE, 16, "XTS requires 128-bit blocks");
+        Self {
+            cipher,
+            tweak_cipher,
+        }
+    }
+
+    /// Creates XTS like [`Xts::new`], but fails if both ciphers use the same key
+    ///
+    /// The keys are compared by encrypting the zero block with both ciphers, in constant time.
+    pub fn new_strict(cipher: &'a C, tweak_cipher: &'a C) -> Result<Self, ModeError> {
+        let xts = Self::new(cipher, tweak_cipher);
+        let mut block = [0; 16];
+        let mut tweak_block = [0; 16];
+        cipher.encrypt_block(&mut block);
+        tweak_cipher.encrypt_block(&mut tweak_block);
+        let equal = bool::from(block.ct_eq(&tweak_block));
+        block.zeroize();
+        tweak_block.zeroize();
+        if equal {
+            Err(ModeError::EqualKeys)
+        } else {
+            Ok(xts)
+        }
+    }
+
+    /// Encrypts the data unit `sector` in place
+    ///
+    /// The data unit must be at least one block and at most 2^20 blocks (16 MiB) long, as
+    /// required by IEEE 1619.
+    pub fn encrypt_sector(&self, sector: u128, buf: &mut [u8]) -> Result<(), ModeError> {
+        self.encrypt(&sector.to_le_bytes(), buf)
+    }
+
+    /// Decrypts the data unit `sector` in place
+    pub fn decrypt_sector(&self, sector: u128, buf: &mut [u8]) -> Result<(), ModeError> {
+        self.decrypt(&sector.to_le_bytes(), buf)
+    }
+
+    /// Encrypts the data unit with the 128-bit tweak value `tweak` in place
+    ///
+    /// [`Xts::encrypt_sector`] uses the little-endian data unit number as tweak value.
+    pub fn encrypt(&self, tweak: &[u8; 16], buf: &mut [u8]) -> Result<(), ModeError> {
+        check_len(buf)?;
+        let mut tweak = self.initial_tweak(tweak);
+        let remainder = buf.len() % 16;
+        let full_len = buf.len() - remainder;
+        for block in buf[..full_len].chunks_exact_mut(16) {
+            self.encrypt_block(&tweak, block);
+            tweak = mul_alpha(tweak);
+        }
+        if remainder != 0 {
+            // the last full ciphertext block is swapped with the padded partial block
+            let (head, partial) = buf.split_at_mut(full_len);
+            let last = &mut head[full_len - 16..];
+            let mut block = [0; 16];
+            block.copy_from_slice(last);
+            block[..remainder].copy_from_slice(partial);
+            partial.copy_from_slice(&last[..remainder]);
+            self.encrypt_block(&tweak, &mut block);
+            last.copy_from_slice(&block);
+            block.zeroize();
+        }
+        tweak.zeroize();
+        Ok(())
+    }
+
+    /// Decrypts the data unit with the 128-bit tweak value `tweak` in place
+    pub fn decrypt(&self, tweak: &[u8; 16], buf: &mut [u8]) -> Result<(), ModeError> {
+        check_len(buf)?;
+        let mut tweak = self.initial_tweak(tweak);
+        let remainder = buf.len() % 16;
+        let full_len = buf.len() - remainder;
+        // with ciphertext stealing, the last full block is decrypted with the following tweak
+        let regular_len = if remainder == 0 {
+            full_len
+        } else {
+            full_len - 16
+        };
+        for block in buf[..regular_len].chunks_exact_mut(16) {
+            self.decrypt_block(&tweak, block);
+            tweak = mul_alpha(tweak);
+        }
+        if remainder != 0 {
+            let mut next_tweak = mul_alpha(tweak);
+            let (head, partial) = buf.split_at_mut(full_len);
+            let last = &mut head[regular_len..];
+            self.decrypt_block(&next_tweak, last);
+            let mut block = [0; 16];
+            block.copy_from_slice(last);
+            block[..remainder].copy_from_slice(partial);
+            partial.copy_from_slice(&last[..remainder]);
+            self.decrypt_block(&tweak, &mut block);
+            last.copy_from_slice(&block);
+            block.zeroize();
+            next_tweak.zeroize();
+        }
+        tweak.zeroize();
+        Ok(())
+    }
+
+    fn initial_tweak(&self, tweak: &[u8; 16]) -> u128 {
+        let mut block = *tweak;
+        self.tweak_cipher.encrypt_block(&mut block);
+        let tweak = u128::from_le_bytes(block);
+        block.zeroize();
+        tweak
+    }
+
+    fn encrypt_block(&self, tweak: &u128, block: &mut [u8]) {
+        let tweak = tweak.to_le_bytes();
+        super::xor(block, &tweak);
+        self.cipher.encrypt_block(block);
+        super::xor(block, &tweak);
+    }
+
+    fn decrypt_block(&self, tweak: &u128, block: &mut [u8]) {
+        let tweak = tweak.to_le_bytes();
+        super::xor(block, &tweak);
+        self.cipher.decrypt_block(block);
+        super::xor(block, &tweak);
+    }
+}
+
+fn check_len(buf: &[u8]) -> Result<(), ModeError> {
+    if buf.len() < 16 || buf.len() > 16 << 20 {
+        Err(ModeError::InvalidLength)
+    } else {
+        Ok(())
+    }
+}
+
+/// Multiplies the tweak by the primitive element in GF(2^128), in constant time
+fn mul_alpha(tweak: u128) -> u128 {
+    let carry = (tweak >> 127).wrapping_neg();
+    (tweak << 1) ^ (carry & 0x87)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes::{Aes128, Aes256};
+    use crate::hex_vec;
+    use crate::twofish::Twofish;
+
+    use quickcheck_macros::quickcheck;
+
+    fn check_vector<C: BlockCipher>(
+        key1: &str,
+        key2: &str,
+        sector: u128,
+        plaintext: &[u8],
+        ciphertext: &str,
+    ) {
+        let cipher = C::new(&hex_vec(key1));
+        let tweak_cipher = C::new(&hex_vec(key2));
+        let xts = Xts::new(&cipher, &tweak_cipher);
+        let mut buf = plaintext.to_vec();
+        xts.encrypt_sector(sector, &mut buf).unwrap();
+        assert_eq!(buf, hex_vec(ciphertext));
+        xts.decrypt_sector(sector, &mut buf).unwrap();
+        assert_eq!(buf, plaintext);
+    }
+
+    // IEEE 1619, appendix B
+
+    /// Plaintext of the 512-byte data units of vectors 4 and 10 to 14
+    fn data_unit() -> Vec<u8> {
+        (0..=255).cycle().take(512).collect()
+    }
+
+    #[test]
+    fn test_xts_aes128() {
+        // vectors 1 to 3
+        check_vector::<Aes128>(
+            "00000000000000000000000000000000",
+            "00000000000000000000000000000000",
+            0,
+            &hex_vec("00000000000000000000000000000000 00000000000000000000000000000000"),
+            "917cf69ebd68b2ec9b9fe9a3eadda692 cd43d2f59598ed858c02c2652fbf922e",
+        );
+        check_vector::<Aes128>(
+            "11111111111111111111111111111111",
+            "22222222222222222222222222222222",
+            0x3333333333,
+            &hex_vec("44444444444444444444444444444444 44444444444444444444444444444444"),
+            "c454185e6a16936e39334038acef838b fb186fff7480adc4289382ecd6d394f0",
+        );
+        check_vector::<Aes128>(
+            "fffefdfcfbfaf9f8f7f6f5f4f3f2f1f0",
+            "22222222222222222222222222222222",
+            0x3333333333,
+            &hex_vec("44444444444444444444444444444444 44444444444444444444444444444444"),
+            "af85336b597afc1a900b2eb21ec949d2 92df4c047e0b21532186a5971a227a89",
+        );
+    }
+
+    #[test]
+    fn test_xts_aes128_data_unit() {
+        // vectors 4 and 5, the plaintext of vector 5 is the ciphertext of vector 4
+        let key1 = "27182818284590452353602874713526";
+        let key2 = "31415926535897932384626433832795";
+        let ciphertext4 = "27a7479befa1d476489f308cd4cfa6e2a96e4bbe3208ff25287dd3819616e89c
+                         c78cf7f5e543445f8333d8fa7f56000005279fa5d8b5e4ad40e736ddb4d35412
+                         328063fd2aab53e5ea1e0a9f332500a5df9487d07a5c92cc512c8866c7e860ce
+                         93fdf166a24912b422976146ae20ce846bb7dc9ba94a767aaef20c0d61ad0265
+                         5ea92dc4c4e41a8952c651d33174be51a10c421110e6d81588ede82103a252d8
+                         a750e8768defffed9122810aaeb99f9172af82b604dc4b8e51bcb08235a6f434
+                         1332e4ca60482a4ba1a03b3e65008fc5da76b70bf1690db4eae29c5f1badd03c
+                         5ccf2a55d705ddcd86d449511ceb7ec30bf12b1fa35b913f9f747a8afd1b130e
+                         94bff94effd01a91735ca1726acd0b197c4e5b03393697e126826fb6bbde8ecc
+                         1e08298516e2c9ed03ff3c1b7860f6de76d4cecd94c8119855ef5297ca67e9f3
+                         e7ff72b1e99785ca0a7e7720c5b36dc6d72cac9574c8cbbc2f801e23e56fd344
+                         b07f22154beba0f08ce8891e643ed995c94d9a69c9f1b5f499027a78572aeebd
+                         74d20cc39881c213ee770b1010e4bea718846977ae119f7a023ab58cca0ad752
+                         afe656bb3c17256a9f6e9bf19fdd5a38fc82bbe872c5539edb609ef4f79c203e
+                         bb140f2e583cb2ad15b4aa5b655016a8449277dbd477ef2c8d6c017db738b18d
+                         eb4a427d1923ce3ff262735779a418f20a282df920147beabe421ee5319d0568";
+        check_vector::<Aes128>(key1, key2, 0, &data_unit(), ciphertext4);
+        check_vector::<Aes128>(
+            key1,
+            key2,
+            1,
+            &hex_vec(ciphertext4),
+            "264d3ca8512194fec312c8c9891f279fefdd608d0c027b60483a3fa811d65ee5
+             9d52d9e40ec5672d81532b38b6b089ce951f0f9c35590b8b978d175213f329bb
+             1c2fd30f2f7f30492a61a532a79f51d36f5e31a7c9a12c286082ff7d2394d18f
+             783e1a8e72c722caaaa52d8f065657d2631fd25bfd8e5baad6e527d763517501
+             c68c5edc3cdd55435c532d7125c8614deed9adaa3acade5888b87bef641c4c99
+             4c8091b5bcd387f3963fb5bc37aa922fbfe3df4e5b915e6eb514717bdd2a7407
+             9a5073f5c4bfd46adf7d282e7a393a52579d11a028da4d9cd9c77124f9648ee3
+             83b1ac763930e7162a8d37f350b2f74b8472cf09902063c6b32e8c2d9290cefb
+             d7346d1c779a0df50edcde4531da07b099c638e83a755944df2aef1aa31752fd
+             323dcb710fb4bfbb9d22b925bc3577e1b8949e729a90bbafeacf7f7879e7b114
+             7e28ba0bae940db795a61b15ecf4df8db07b824bb062802cc98a9545bb2aaeed
+             77cb3fc6db15dcd7d80d7d5bc406c4970a3478ada8899b329198eb61c193fb62
+             75aa8ca340344a75a862aebe92eee1ce032fd950b47d7704a3876923b4ad6284
+             4bf4a09c4dbe8b4397184b7471360c9564880aedddb9baa4af2e75394b08cd32
+             ff479c57a07d3eab5d54de5f9738b8d27f27a9f0ab11799d7b7ffefb2704c95c
+             6ad12c39f1e867a4b7b1d7818a4b753dfd2a89ccb45e001a03a867b187f225dd",
+        );
+    }
+
+    #[test]
+    fn test_xts_aes256() {
+        // vectors 10 to 14
+        let key1 = "2718281828459045235360287471352662497757247093699959574966967627";
+        let key2 = "3141592653589793238462643383279502884197169399375105820974944592";
+        check_vector::<Aes256>(
+            key1,
+            key2,
+            0xff,
+            &data_unit(),
+            "1c3b3a102f770386e4836c99e370cf9bea00803f5e482357a4ae12d414a3e63b
+             5d31e276f8fe4a8d66b317f9ac683f44680a86ac35adfc3345befecb4bb188fd
+             5776926c49a3095eb108fd1098baec70aaa66999a72a82f27d848b21d4a741b0
+             c5cd4d5fff9dac89aeba122961d03a757123e9870f8acf1000020887891429ca
+             2a3e7a7d7df7b10355165c8b9a6d0a7de8b062c4500dc4cd120c0f7418dae3d0
+             b5781c34803fa75421c790dfe1de1834f280d7667b327f6c8cd7557e12ac3a0f
+             93ec05c52e0493ef31a12d3d9260f79a289d6a379bc70c50841473d1a8cc81ec
+             583e9645e07b8d9670655ba5bbcfecc6dc3966380ad8fecb17b6ba02469a020a
+             84e18e8f84252070c13e9f1f289be54fbc481457778f616015e1327a02b140f1
+             505eb309326d68378f8374595c849d84f4c333ec4423885143cb47bd71c5edae
+             9be69a2ffeceb1bec9de244fbe15992b11b77c040f12bd8f6a975a44a0f90c29
+             a9abc3d4d893927284c58754cce294529f8614dcd2aba991925fedc4ae74ffac
+             6e333b93eb4aff0479da9a410e4450e0dd7ae4c6e2910900575da401fc07059f
+             645e8b7e9bfdef33943054ff84011493c27b3429eaedb4ed5376441a77ed4385
+             1ad77f16f541dfd269d50d6a5f14fb0aab1cbb4c1550be97f7ab4066193c4caa
+             773dad38014bd2092fa755c824bb5e54c4f36ffda9fcea70b9c6e693e148c151",
+        );
+        check_vector::<Aes256>(
+            key1,
+            key2,
+            0xffff,
+            &data_unit(),
+            "77a31251618a15e6b92d1d66dffe7b50b50bad552305ba0217a610688eff7e11
+             e1d0225438e093242d6db274fde801d4cae06f2092c728b2478559df58e837c2
+             469ee4a4fa794e4bbc7f39bc026e3cb72c33b0888f25b4acf56a2a9804f1ce6d
+             3d6e1dc6ca181d4b546179d55544aa7760c40d06741539c7e3cd9d2f6650b201
+             3fd0eeb8c2b8e3d8d240ccae2d4c98320a7442e1c8d75a42d6e6cfa4c2eca179
+             8d158c7aecdf82490f24bb9b38e108bcda12c3faf9a21141c3613b58367f922a
+             aa26cd22f23d708dae699ad7cb40a8ad0b6e2784973dcb605684c08b8d6998c6
+             9aac049921871ebb65301a4619ca80ecb485a31d744223ce8ddc2394828d6a80
+             470c092f5ba413c3378fa6054255c6f9df4495862bbb3287681f931b687c888a
+             bf844dfc8fc28331e579928cd12bd2390ae123cf03818d14dedde5c0c24c8ab0
+             18bfca75ca096f2d531f3d1619e785f1ada437cab92e980558b3dce1474afb75
+             bfedbf8ff54cb2618e0244c9ac0d3c66fb51598cd2db11f9be39791abe447c63
+             094f7c453b7ff87cb5bb36b7c79efb0872d17058b83b15ab0866ad8a58656c5a
+             7e20dbdf308b2461d97c0ec0024a2715055249cf3b478ddd4740de654f75ca68
+             6e0d7345c69ed50cdc2a8b332b1f8824108ac937eb050585608ee734097fc090
+             54fbff89eeaeea791f4a7ab1f9868294a4f9e27b42af8100cb9d59cef9645803",
+        );
+        check_vector::<Aes256>(
+            key1,
+            key2,
+            0xffffff,
+            &data_unit(),
+            "e387aaa58ba483afa7e8eb469778317ecf4cf573aa9d4eac23f2cdf914e4e200
+             a8b490e42ee646802dc6ee2b471b278195d60918ececb44bf79966f83faba049
+             9298ebc699c0c8634715a320bb4f075d622e74c8c932004f25b41e361025b5a8
+             7815391f6108fc4afa6a05d9303c6ba68a128a55705d415985832fdeaae6c8e1
+             9110e84d1b1f199a2692119edc96132658f09da7c623efcec712537a3d94c0bf
+             5d7e352ec94ae5797fdb377dc1551150721adf15bd26a8efc2fcaad56881fa9e
+             62462c28f30ae1ceaca93c345cf243b73f542e2074a705bd2643bb9f7cc79bb6
+             e7091ea6e232df0f9ad0d6cf502327876d82207abf2115cdacf6d5a48f6c1879
+             a65b115f0f8b3cb3c59d15dd8c769bc014795a1837f3901b5845eb491adfefe0
+             97b1fa30a12fc1f65ba22905031539971a10f2f36c321bb51331cdefb39e3964
+             c7ef079994f5b69b2edd83a71ef549971ee93f44eac3938fcdd61d01fa71799d
+             a3a8091c4c48aa9ed263ff0749df95d44fef6a0bb578ec69456aa5408ae32c7a
+             f08ad7ba8921287e3bbee31b767be06a0e705c864a769137df28292283ea81a2
+             480241b44d9921cdbec1bc28dc1fda114bd8e5217ac9d8ebafa720e9da4f9ace
+             231cc949e5b96fe76ffc21063fddc83a6b8679c00d35e09576a875305bed5f36
+             ed242c8900dd1fa965bc950dfce09b132263a1eef52dd6888c309f5a7d712826",
+        );
+        check_vector::<Aes256>(
+            key1,
+            key2,
+            0xffffffff,
+            &data_unit(),
+            "bf53d2dade78e822a4d949a9bc6766b01b06a8ef70d26748c6a7fc36d80ae4c5
+             520f7c4ab0ac8544424fa405162fef5a6b7f229498063618d39f0003cb5fb8d1
+             c86b643497da1ff945c8d3bedeca4f479702a7a735f043ddb1d6aaade3c4a0ac
+             7ca7f3fa5279bef56f82cd7a2f38672e824814e10700300a055e1630b8f1cb0e
+             919f5e942010a416e2bf48cb46993d3cb6a51c19bacf864785a00bc2ecff15d3
+             50875b246ed53e68be6f55bd7e05cfc2b2ed6432198a6444b6d8c247fab941f5
+             69768b5c429366f1d3f00f0345b96123d56204c01c63b22ce78baf116e525ed9
+             0fdea39fa469494d3866c31e05f295ff21fea8d4e6e13d67e47ce722e9698a1c
+             1048d68ebcde76b86fcf976eab8aa9790268b7068e017a8b9b749409514f1053
+             027fd16c3786ea1bac5f15cb79711ee2abe82f5cf8b13ae73030ef5b9e4457e7
+             5d1304f988d62dd6fc4b94ed38ba831da4b7634971b6cd8ec325d9c61c00f1df
+             73627ed3745a5e8489f3a95c69639c32cd6e1d537a85f75cc844726e8a72fc00
+             77ad22000f1d5078f6b866318c668f1ad03d5a5fced5219f2eabbd0aa5c0f460
+             d183f04404a0d6f469558e81fab24a167905ab4c7878502ad3e38fdbe62a4155
+             6cec37325759533ce8f25f367c87bb5578d667ae93f9e2fd99bcbc5f2fbba88c
+             f6516139420fcff3b7361d86322c4bd84c82f335abb152c4a93411373aaa8220",
+        );
+        check_vector::<Aes256>(
+            key1,
+            key2,
+            0xffffffffff,
+            &data_unit(),
+            "64497e5a831e4a932c09be3e5393376daa599548b816031d224bbf50a818ed23
+             50eae7e96087c8a0db51ad290bd00c1ac1620857635bf246c176ab463be30b80
+             8da548081ac847b158e1264be25bb0910bbc92647108089415d45fab1b3d2604
+             e8a8eff1ae4020cfa39936b66827b23f371b92200be90251e6d73c5f86de5fd4
+             a950781933d79a28272b782a2ec313efdfcc0628f43d744c2dc2ff3dcb66999b
+             50c7ca895b0c64791eeaa5f29499fb1c026f84ce5b5c72ba1083cddb5ce45434
+             631665c333b60b11593fb253c5179a2c8db813782a004856a1653011e93fb6d8
+             76c18366dd8683f53412c0c180f9c848592d593f8609ca736317d356e13e2bff
+             3a9f59cd9aeb19cd482593d8c46128bb32423b37a9adfb482b99453fbe25a41b
+             f6feb4aa0bef5ed24bf73c762978025482c13115e4015aac992e5613a3b5c2f6
+             85b84795cb6e9b2656d8c88157e52c42f978d8634c43d06fea928f2822e465aa
+             6576e9bf419384506cc3ce3c54ac1a6f67dc66f3b30191e698380bc999b05abc
+             e19dc0c6dcc2dd001ec535ba18deb2df1a101023108318c75dc98611a09dc48a
+             0acdec676fabdf222f07e026f059b672b56e5cbc8e1d21bbd867dd9272120546
+             81d70ea737134cdfce93b6f82ae22423274e58a0821cc5502e2d0ab4585e94de
+             6975be5e0b4efce51cd3e70c25a1fbbbd609d273ad5b0d59631c531f6a0a57b9",
+        );
+    }
+
+    #[test]
+    fn test_ciphertext_stealing() {
+        // vectors 15 to 18
+        let key1 = "fffefdfcfbfaf9f8f7f6f5f4f3f2f1f0";
+        let key2 = "bfbebdbcbbbab9b8b7b6b5b4b3b2b1b0";
+        let sector = 0x123456789a;
+        check_vector::<Aes128>(
+            key1,
+            key2,
+            sector,
+            &hex_vec("000102030405060708090a0b0c0d0e0f10"),
+            "6c1625db4671522d3d7599601de7ca09ed",
+        );
+        check_vector::<Aes128>(
+            key1,
+            key2,
+            sector,
+            &hex_vec("000102030405060708090a0b0c0d0e0f1011"),
+            "d069444b7a7e0cab09e24447d24deb1fedbf",
+        );
+        check_vector::<Aes128>(
+            key1,
+            key2,
+            sector,
+            &hex_vec("000102030405060708090a0b0c0d0e0f101112"),
+            "e5df1351c0544ba1350b3363cd8ef4beedbf9d",
+        );
+        check_vector::<Aes128>(
+            key1,
+            key2,
+            sector,
+            &hex_vec("000102030405060708090a0b0c0d0e0f10111213"),
+            "9d84c813f719aa2c7be3f66171c7c5c2edbf9dac",
+        );
+    }
+
+    #[test]
+    fn test_short_data_unit() {
+        let cipher = <Aes128 as BlockCipher>::new(&[1; 16]);
+        let tweak_cipher = <Aes128 as BlockCipher>::new(&[2; 16]);
+        let xts = Xts::new(&cipher, &tweak_cipher);
+        assert_eq!(
+            xts.encrypt_sector(0, &mut [0; 15]),
+            Err(ModeError::InvalidLength)
+        );
+        assert_eq!(
+            xts.decrypt_sector(0, &mut []),
+            Err(ModeError::InvalidLength)
+        );
+    }
+
+    #[test]
+    fn test_long_data_unit() {
+        let cipher = <Aes128 as BlockCipher>::new(&[1; 16]);
+        let tweak_cipher = <Aes128 as BlockCipher>::new(&[2; 16]);
+        let xts = Xts::new(&cipher, &tweak_cipher);
+        let mut buf = vec![0; (16 << 20) + 1];
+        assert_eq!(
+            xts.encrypt_sector(0, &mut buf),
+            Err(ModeError::InvalidLength)
+        );
+        assert_eq!(
+            xts.decrypt_sector(0, &mut buf),
+            Err(ModeError::InvalidLength)
+        );
+        assert!(buf.iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn test_equal_keys() {
+        let cipher = <Aes128 as BlockCipher>::new(&[1; 16]);
+        let tweak_cipher = <Aes128 as BlockCipher>::new(&[2; 16]);
+        assert!(Xts::new_strict(&cipher, &tweak_cipher).is_ok());
+        let same_key = <Aes128 as BlockCipher>::new(&[1; 16]);
+        assert_eq!(
+            Xts::new_strict(&cipher, &same_key).err(),
+            Some(ModeError::EqualKeys)
+        );
+        assert_eq!(
+            Xts::new_strict(&cipher, &cipher).err(),
+            Some(ModeError::EqualKeys)
+        );
+    }
+
+    #[quickcheck]
+    fn test_roundtrip(msg: Vec<u8>, sector: u128) {
+        let cipher = <Twofish as BlockCipher>::new(&[1; 32]);
+        let tweak_cipher = <Twofish as BlockCipher>::new(&[2; 32]);
+        let xts = Xts::new(&cipher, &tweak_cipher);
+        let mut buf = msg.clone();
+        buf.extend_from_slice(&[3; 16]);
+        xts.encrypt_sector(sector, &mut buf).unwrap();
+        assert_ne!(buf[msg.len()..], [3; 16]);
+
+        let mut other_sector = msg.clone();
+        other_sector.extend_from_slice(&[3; 16]);
+        xts.encrypt_sector(sector.wrapping_add(1), &mut other_sector)
+            .unwrap();
+        assert_ne!(buf, other_sector);
+
+        xts.decrypt_sector(sector, &mut buf).unwrap();
+        assert_eq!(buf[..msg.len()], msg[..]);
+    }
+}
@@ -0,0 +1,366 @@
+//! Lookup of ciphers and hash functions by name
+//!
+//! The registry lists every block cipher and hash function of the crate with its name, aliases
+//! and sizes, and creates boxed instances by name, so that algorithms can be chosen at runtime,
+//! e.g. from command line arguments or configuration files. Names are matched
+//! case-insensitively.
+
+use crate::aes::{Aes128, Aes192, Aes256};
+use crate::ct::Secret;
+use crate::des::{Des, TripleDes};
+use crate::desx::DesX;
+use crate::md5::Md5;
+use crate::sha1::Sha1;
+use crate::twofish::Twofish;
+use crate::{BlockCipher, Hasher};
+
+use std::fmt;
+use std::ops::RangeInclusive;
+
+/// Object safe version of [`BlockCipher`]
+pub trait DynBlockCipher {
+    /// Block size in bytes
+    fn block_size(&self) -> usize;
+
+    /// Encrypts a single block in place
+    fn encrypt_block(&self, block: &mut [u8]);
+
+    /// Decrypts a single block in place
+    fn decrypt_block(&self, block: &mut [u8]);
+}
+
+impl<T: BlockCipher> DynBlockCipher for T {
+    fn block_size(&self) -> usize {
+        T::BLOCK_SIZE
+    }
+
+    fn encrypt_block(&self, block: &mut [u8]) {
+        BlockCipher::encrypt_block(self, block);
+    }
+
+    fn decrypt_block(&self, block: &mut [u8]) {
+        BlockCipher::decrypt_block(self, block);
+    }
+}
+
+/// Object safe version of [`Hasher`]
+pub trait DynHasher {
+    /// Output size in bytes
+    fn output_size(&self) -> usize;
+
+    /// Internal block size in bytes
+    fn block_size(&self) -> usize;
+
+    fn update(&mut self, input: &[u8]);
+
+    fn finalize(self: Box<Self>) -> Secret<Vec<u8>>;
+
+    /// Computes the hash and resets the state for hashing a new message
+    fn finalize_reset(&mut self) -> Secret<Vec<u8>>;
+
+    /// Resets the state, discarding all input absorbed so far
+    fn reset(&mut self);
+}
+
+impl<T: Hasher> DynHasher for T {
+    fn output_size(&self) -> usize {
+        T::OUTPUT_SIZE
+    }
+
+    fn block_size(&self) -> usize {
+        T::BLOCK_SIZE
+    }
+
+    fn update(&mut self, input: &[u8]) {
+        Hasher::update(self, input);
+    }
+
+    fn finalize(self: Box<Self>) -> Secret<Vec<u8>> {
+        Secret::new(Hasher::finalize(*self).as_ref().to_vec())
+    }
+
+    fn finalize_reset(&mut self) -> Secret<Vec<u8>> {
+        Secret::new(Hasher::finalize_reset(self).as_ref().to_vec())
+    }
+
+    fn reset(&mut self) {
+        Hasher::reset(self);
+    }
+}
+
+/// Error for failed lookups
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegistryError {
+    /// No algorithm with this name exists
+    UnknownAlgorithm(String),
+    /// The key size is not supported by the cipher
+    InvalidKeySize {
+        algorithm: &'static str,
+        key_size: usize,
+    },
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RegistryError::UnknownAlgorithm(name) => write!(f, "unknown algorithm: {}", name),
+            RegistryError::InvalidKeySize {
+                algorithm,
+                key_size,
+            } => write!(f, "invalid key size for {}: {} bytes", algorithm, key_size),
+        }
+    }
+}
+
+impl std::error::Error for RegistryError {}
+
+/// Block cipher entry of the registry
+pub struct CipherInfo {
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    /// Block size in bytes
+    pub block_size: usize,
+    /// Ranges of supported key sizes in bytes
+    pub key_sizes: &'static [RangeInclusive<usize>],
+    new: fn(&[u8]) -> Box<dyn DynBlockCipher>,
+}
+
+impl CipherInfo {
+    /// Returns whether the cipher accepts keys of `key_size` bytes
+    pub fn supports_key_size(&self, key_size: usize) -> bool {
+        self.key_sizes.iter().any(|sizes| sizes.contains(&key_size))
+    }
+
+    /// Creates the cipher keyed with `key`
+    pub fn create(&self, key: &[u8]) -> Result<Box<dyn DynBlockCipher>, RegistryError> {
+        if !self.supports_key_size(key.len()) {
+            return Err(RegistryError::InvalidKeySize {
+                algorithm: self.name,
+                key_size: key.len(),
+            });
+        }
+        Ok((self.new)(key))
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        matches(self.name, self.aliases, name)
+    }
+}
+
+/// Hash function entry of the registry
+pub struct HashInfo {
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    /// Internal block size in bytes
+    pub block_size: usize,
+    /// Output size in bytes
+    pub output_size: usize,
+    new: fn() -> Box<dyn DynHasher>,
+}
+
+impl HashInfo {
+    /// Creates the hash function in its initial state
+    pub fn create(&self) -> Box<dyn DynHasher> {
+        (self.new)()
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        matches(self.name, self.aliases, name)
+    }
+}
+
+fn matches(info_name: &str, aliases: &[&str], name: &str) -> bool {
+    info_name.eq_ignore_ascii_case(name) || aliases.iter().any(|a| a.eq_ignore_ascii_case(name))
+}
+
+fn new_cipher_boxed<T: BlockCipher + 'static>(key: &[u8]) -> Box<dyn DynBlockCipher> {
+    Box::new(<T as BlockCipher>::new(key))
+}
+
+fn new_hasher_boxed<T: Hasher + 'static>() -> Box<dyn DynHasher> {
+    Box::new(T::default())
+}
+
+static CIPHERS: [CipherInfo; 7] = [
+    CipherInfo {
+        name: "aes128",
+        aliases: &["aes-128"],
+        block_size: Aes128::BLOCK_SIZE,
+        key_sizes: &[16..=16],
+        new: new_cipher_boxed::<Aes128>,
+    },
+    CipherInfo {
+        name: "aes192",
+        aliases: &["aes-192"],
+        block_size: Aes192::BLOCK_SIZE,
+        key_sizes: &[24..=24],
+        new: new_cipher_boxed::<Aes192>,
+    },
+    CipherInfo {
+        name: "aes256",
+        aliases: &["aes-256"],
+        block_size: Aes256::BLOCK_SIZE,
+        key_sizes: &[32..=32],
+        new: new_cipher_boxed::<Aes256>,
+    },
+    CipherInfo {
+        name: "des",
+        aliases: &[],
+        block_size: Des::BLOCK_SIZE,
+        key_sizes: &[8..=8],
+        new: new_cipher_boxed::<Des>,
+    },
+    CipherInfo {
+        name: "3des",
+        aliases: &["tdes", "tdea", "des-ede3", "triple-des"],
+        block_size: TripleDes::BLOCK_SIZE,
+        key_sizes: &[16..=16, 24..=24],
+        new: new_cipher_boxed::<TripleDes>,
+    },
+    CipherInfo {
+        name: "desx",
+        aliases: &["des-x"],
+        block_size: DesX::BLOCK_SIZE,
+        key_sizes: &[24..=24],
+        new: new_cipher_boxed::<DesX>,
+    },
+    CipherInfo {
+        name: "twofish",
+        aliases: &[],
+        block_size: Twofish::BLOCK_SIZE,
+        // shorter keys are padded, see `twofish::Key::from_slice`
+        key_sizes: &[0..=32],
+        new: new_cipher_boxed::<Twofish>,
+    },
+];
+
+static HASHES: [HashInfo; 2] = [
+    HashInfo {
+        name: "md5",
+        aliases: &[],
+        block_size: Md5::BLOCK_SIZE,
+        output_size: Md5::OUTPUT_SIZE,
+        new: new_hasher_boxed::<Md5>,
+    },
+    HashInfo {
+        name: "sha1",
+        aliases: &["sha-1"],
+        block_size: Sha1::BLOCK_SIZE,
+        output_size: Sha1::OUTPUT_SIZE,
+        new: new_hasher_boxed::<Sha1>,
+    },
+];
+
+/// Lists all block ciphers
+pub fn ciphers() -> &'static [CipherInfo] {
+    &CIPHERS
+}
+
+/// Lists all hash functions
+pub fn hashes() -> &'static [HashInfo] {
+    &HASHES
+}
+
+/// Looks up a block cipher by name or alias
+pub fn find_cipher(name: &str) -> Option<&'static CipherInfo> {
+    CIPHERS.iter().find(|info| info.matches(name))
+}
+
+/// Looks up a hash function by name or alias
+pub fn find_hash(name: &str) -> Option<&'static HashInfo> {
+    HASHES.iter().find(|info| info.matches(name))
+}
+
+/// Creates the block cipher `name` keyed with `key`
+pub fn new_cipher(name: &str, key: &[u8]) -> Result<Box<dyn DynBlockCipher>, RegistryError> {
+    find_cipher(name)
+        .ok_or_else(|| RegistryError::UnknownAlgorithm(name.to_string()))?
+        .create(key)
+}
+
+/// Creates the hash function `name`
+pub fn new_hash(name: &str) -> Result<Box<dyn DynHasher>, RegistryError> {
+    find_hash(name)
+        .map(HashInfo::create)
+        .ok_or_else(|| RegistryError::UnknownAlgorithm(name.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex;
+
+    #[test]
+    fn test_new_cipher() {
+        let key: [u8; 16] = hex("000102030405060708090a0b0c0d0e0f").unwrap();
+        let cipher = new_cipher("AES-128", &key).unwrap();
+        assert_eq!(cipher.block_size(), 16);
+
+        let mut block: [u8; 16] = hex("00112233445566778899aabbccddeeff").unwrap();
+        cipher.encrypt_block(&mut block);
+        assert_eq!(block, hex("69c4e0d86a7b0430d8cdb78070b4c55a").unwrap());
+        cipher.decrypt_block(&mut block);
+        assert_eq!(block, hex("00112233445566778899aabbccddeeff").unwrap());
+
+        assert_eq!(
+            new_cipher("des", &key).err(),
+            Some(RegistryError::InvalidKeySize {
+                algorithm: "des",
+                key_size: 16
+            })
+        );
+        assert!(new_cipher("twofish", &[0; 10]).is_ok());
+        assert_eq!(
+            new_cipher("twofish", &[0; 33]).err(),
+            Some(RegistryError::InvalidKeySize {
+                algorithm: "twofish",
+                key_size: 33
+            })
+        );
+        assert_eq!(
+            new_cipher("rot13", &key).err(),
+            Some(RegistryError::UnknownAlgorithm("rot13".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_new_hash() {
+        let mut hasher = new_hash("SHA-1").unwrap();
+        assert_eq!(hasher.output_size(), 20);
+        hasher.update(b"abc");
+        assert_eq!(
+            hasher.finalize_reset().expose()[..],
+            hex::<20>("a9993e364706816aba3e25717850c26c9cd0d89d").unwrap()
+        );
+        assert_eq!(hasher.finalize().as_ref(), crate::sha1::sha1(b"").as_ref());
+        assert!(new_hash("sha256").is_err());
+    }
+
+    #[test]
+    fn test_all_entries() {
+        for info in ciphers() {
+            assert_eq!(find_cipher(info.name).unwrap().name, info.name);
+            for alias in info.aliases {
+                assert_eq!(find_cipher(alias).unwrap().name, info.name);
+            }
+            for key_size in info.key_sizes.iter().cloned().flatten() {
+                let cipher = info.create(&vec![0x42; key_size]).unwrap();
+                assert_eq!(cipher.block_size(), info.block_size);
+                let mut block = vec![0; info.block_size];
+                cipher.encrypt_block(&mut block);
+                cipher.decrypt_block(&mut block);
+                assert_eq!(block, vec![0; info.block_size]);
+            }
+        }
+        for info in hashes() {
+            assert_eq!(find_hash(info.name).unwrap().name, info.name);
+            for alias in info.aliases {
+                assert_eq!(find_hash(alias).unwrap().name, info.name);
+            }
+            let hasher = info.create();
+            assert_eq!(hasher.output_size(), info.output_size);
+            assert_eq!(hasher.block_size(), info.block_size);
+            assert_eq!(hasher.finalize().expose().len(), info.output_size);
+        }
+    }
+}
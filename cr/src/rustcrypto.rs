@@ -0,0 +1,203 @@
+//! Implementations of the RustCrypto `digest` and `cipher` traits
+//!
+//! With the `digest` feature, `Md5` and `Sha1` implement `digest::Digest` and can be used with
+//! e.g. `hmac` or `pbkdf2`. With the `cipher` feature, the block ciphers implement
+//! `cipher::BlockEncrypt` and `cipher::BlockDecrypt` and can be used with the block mode crates.
+
+#[cfg(feature = "digest")]
+mod digest_impls {
+    use crate::md5::Md5;
+    use crate::sha1::Sha1;
+
+    use digest::consts::{U16, U20, U64};
+    use digest::{BlockInput, FixedOutput, Output, Reset, Update};
+
+    macro_rules! impl_digest {
+        ($name:ident, $output_size:ty) => {
+            impl Update for $name {
+                fn update(&mut self, data: impl AsRef<[u8]>) {
+                    $name::update(self, data.as_ref());
+                }
+            }
+
+            impl BlockInput for $name {
+                type BlockSize = U64;
+            }
+
+            impl FixedOutput for $name {
+                type OutputSize = $output_size;
+
+                fn finalize_into(self, out: &mut Output<Self>) {
+                    out.copy_from_slice(self.digest().expose());
+                }
+
+                fn finalize_into_reset(&mut self, out: &mut Output<Self>) {
+                    out.copy_from_slice(core::mem::take(self).digest().expose());
+                }
+            }
+
+            impl Reset for $name {
+                fn reset(&mut self) {
+                    *self = $name::new();
+                }
+            }
+        };
+    }
+
+    impl_digest!(Md5, U16);
+    impl_digest!(Sha1, U20);
+}
+
+#[cfg(feature = "cipher")]
+mod cipher_impls {
+    use crate::aes::{Aes128, Aes192, Aes256};
+    use crate::des::{Des, TripleDes};
+    use crate::desx::DesX;
+    use crate::twofish::Twofish;
+
+    use cipher::consts::{U1, U16, U24, U32, U8};
+    use cipher::errors::InvalidLength;
+    use cipher::generic_array::typenum::Unsigned;
+    use cipher::{Block, BlockCipher, BlockCipherKey, BlockDecrypt, BlockEncrypt, NewBlockCipher};
+
+    macro_rules! impl_cipher {
+        ($name:ident, $block_size:ty, $key_size:ty) => {
+            impl_cipher!($name, $block_size, $key_size, |len| len
+                == <$key_size>::USIZE);
+        };
+        ($name:ident, $block_size:ty, $key_size:ty, $is_valid_key_len:expr) => {
+            impl BlockCipher for $name {
+                type BlockSize = $block_size;
+                type ParBlocks = U1;
+            }
+
+            impl BlockEncrypt for $name {
+                fn encrypt_block(&self, block: &mut Block<Self>) {
+                    crate::BlockCipher::encrypt_block(self, block);
+                }
+            }
+
+            impl BlockDecrypt for $name {
+                fn decrypt_block(&self, block: &mut Block<Self>) {
+                    crate::BlockCipher::decrypt_block(self, block);
+                }
+            }
+
+            impl NewBlockCipher for $name {
+                type KeySize = $key_size;
+
+                fn new(key: &BlockCipherKey<Self>) -> Self {
+                    crate::BlockCipher::new(key)
+                }
+
+                fn new_from_slice(key: &[u8]) -> Result<Self, InvalidLength> {
+                    if ($is_valid_key_len)(key.len()) {
+                        Ok(crate::BlockCipher::new(key))
+                    } else {
+                        Err(InvalidLength)
+                    }
+                }
+            }
+        };
+    }
+
+    impl_cipher!(Aes128, U16, U16);
+    impl_cipher!(Aes192, U16, U24);
+    impl_cipher!(Aes256, U16, U32);
+    impl_cipher!(Des, U8, U8);
+    impl_cipher!(TripleDes, U8, U24, |len| len == 16 || len == 24);
+    impl_cipher!(DesX, U8, U24);
+    impl_cipher!(Twofish, U16, U32, |len| len <= 32);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hex;
+
+    #[cfg(feature = "digest")]
+    #[test]
+    fn test_digest() {
+        use crate::md5::Md5;
+        use crate::sha1::Sha1;
+        use digest::Digest;
+
+        assert_eq!(
+            <Md5 as Digest>::digest(b"abc")[..],
+            hex::<16>("900150983cd24fb0d6963f7d28e17f72").unwrap()
+        );
+        assert_eq!(
+            <Sha1 as Digest>::digest(b"abc")[..],
+            hex::<20>("a9993e364706816aba3e25717850c26c9cd0d89d").unwrap()
+        );
+
+        let mut hasher = <Sha1 as Digest>::new();
+        Digest::update(&mut hasher, b"abc");
+        assert_eq!(
+            hasher.finalize_reset()[..],
+            hex::<20>("a9993e364706816aba3e25717850c26c9cd0d89d").unwrap()
+        );
+        assert_eq!(
+            hasher.finalize()[..],
+            hex::<20>("da39a3ee5e6b4b0d3255bfef95601890afd80709").unwrap()
+        );
+    }
+
+    #[cfg(feature = "digest")]
+    #[test]
+    fn test_hmac() {
+        use hmac::{Hmac, Mac, NewMac};
+
+        // RFC 2202, test case 2
+        let mut mac = Hmac::<crate::md5::Md5>::new_from_slice(b"Jefe").unwrap();
+        mac.update(b"what do ya want for nothing?");
+        assert_eq!(
+            mac.finalize().into_bytes()[..],
+            hex::<16>("750c783e6ab0b503eaa86e310a5db738").unwrap()
+        );
+
+        let mut mac = Hmac::<crate::sha1::Sha1>::new_from_slice(b"Jefe").unwrap();
+        mac.update(b"what do ya want for nothing?");
+        assert_eq!(
+            mac.finalize().into_bytes()[..],
+            hex::<20>("effcdf6ae5eb2fa2d27416d5f184df9c259a7c79").unwrap()
+        );
+    }
+
+    #[cfg(feature = "cipher")]
+    #[test]
+    fn test_cipher() {
+        use crate::aes::Aes128;
+        use crate::des::{Des, TripleDes};
+        use crate::twofish::Twofish;
+        use cipher::{Block, BlockCipherKey, BlockDecrypt, BlockEncrypt, NewBlockCipher};
+
+        let mut key = BlockCipherKey::<Aes128>::default();
+        key.copy_from_slice(&hex::<16>("000102030405060708090a0b0c0d0e0f").unwrap());
+        let cipher = <Aes128 as NewBlockCipher>::new(&key);
+        let mut block = Block::<Aes128>::default();
+        block.copy_from_slice(&hex::<16>("00112233445566778899aabbccddeeff").unwrap());
+        BlockEncrypt::encrypt_block(&cipher, &mut block);
+        assert_eq!(
+            block[..],
+            hex::<16>("69c4e0d86a7b0430d8cdb78070b4c55a").unwrap()
+        );
+        BlockDecrypt::decrypt_block(&cipher, &mut block);
+        assert_eq!(
+            block[..],
+            hex::<16>("00112233445566778899aabbccddeeff").unwrap()
+        );
+
+        let cipher = Twofish::new_from_slice(&[]).unwrap();
+        let mut block = Block::<Twofish>::default();
+        BlockEncrypt::encrypt_block(&cipher, &mut block);
+        assert_eq!(
+            block[..],
+            hex::<16>("9F589F5CF6122C32B6BFEC2F2AE8C35A").unwrap()
+        );
+
+        assert!(TripleDes::new_from_slice(&[0; 16]).is_ok());
+        assert!(TripleDes::new_from_slice(&[0; 8]).is_err());
+        assert!(Des::new_from_slice(&[0; 7]).is_err());
+        assert!(Twofish::new_from_slice(&[0; 33]).is_err());
+    }
+}
@@ -3,14 +3,66 @@
 //! https://datatracker.ietf.org/doc/html/rfc3174
 #![allow(clippy::many_single_char_names)]
 
-use std::convert::TryInto;
+use crate::ct::Secret;
+use crate::midstate::{self, MidstateError};
+use crate::zeroize::Zeroize;
+use crate::Hasher;
 
-pub fn sha1(data: &[u8]) -> [u8; 20] {
+use core::convert::TryInto;
+
+/// Size of an exported midstate in bytes, see [`crate::midstate`]
+pub const MIDSTATE_SIZE: usize = 95;
+
+/// Computes the SHA-1 hash of `data`
+pub fn sha1(data: &[u8]) -> Secret<[u8; 20]> {
     let mut state = Sha1::new();
     state.update(data);
     state.digest()
 }
 
+/// Computes the SHA-1 hash of `data` in a const context
+///
+/// This can be used to compute hashes at compile time, e.g. of embedded data. It is slower than
+/// [`sha1`] and does not wipe its intermediate state, so it must not be used for secret data.
+pub const fn sha1_const(data: &[u8]) -> [u8; 20] {
+    let mut state = INITIAL_STATE;
+    let mut block = [0; 64];
+    let mut w = [0; 16];
+    let mut h = [0; 5];
+
+    // the padded message consists of the data, the padding and the 8 bytes length
+    let len = data.len();
+    let pad_len = if len % 64 < 56 {
+        56 - len % 64
+    } else {
+        120 - len % 64
+    };
+    let len_bits = ((len as u64) << 3).to_be_bytes();
+
+    let mut i = 0;
+    while i < len + pad_len + 8 {
+        block[i % 64] = if i < len {
+            data[i]
+        } else if i == len {
+            0x80
+        } else if i < len + pad_len {
+            0
+        } else {
+            len_bits[i - len - pad_len]
+        };
+        i += 1;
+        if i % 64 == 0 {
+            compress_with(&mut state, &block, &mut w, &mut h);
+        }
+    }
+
+    encode(&state)
+}
+
+const INITIAL_STATE: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+/// SHA-1 hash state, wiped on drop
+#[derive(Clone)]
 pub struct Sha1 {
     state: [u32; 5],
     len: u64, // number of bytes
@@ -21,14 +73,14 @@ pub struct Sha1 {
 impl Sha1 {
     pub fn new() -> Self {
         Self {
-            state: [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0],
+            state: INITIAL_STATE,
             len: 0,
             block_idx: 0,
             block: [0; 64],
         }
     }
 
-    pub fn update(&mut self, mut input: &[u8]) {
+    pub fn update(&mut self, mut input: &[u8]) -> &mut Self {
         self.len += input.len() as u64;
 
         let remaining = 64 - self.block_idx;
@@ -37,7 +89,7 @@ impl Sha1 {
             let n = input.len();
             self.block[self.block_idx..self.block_idx + n].copy_from_slice(input);
             self.block_idx += n;
-            return;
+            return self;
         }
 
         if self.block_idx != 0 {
@@ -59,26 +111,56 @@ impl Sha1 {
         let remainder = chunks.remainder();
         self.block[0..remainder.len()].copy_from_slice(remainder);
         self.block_idx = remainder.len();
+
+        self
     }
 
-    pub fn digest(mut self) -> [u8; 20] {
+    pub fn digest(mut self) -> Secret<[u8; 20]> {
         self.pad();
-        let mut res = [0; 20];
-        for i in 0..5 {
-            let bytes = self.state[i].to_le_bytes();
-            res[4 * i] = bytes[3];
-            res[4 * i + 1] = bytes[2];
-            res[4 * i + 2] = bytes[1];
-            res[4 * i + 3] = bytes[0];
+        Secret::new(encode(&self.state))
+    }
+
+    /// Exports the state of the partially computed hash, see [`crate::midstate`]
+    pub fn export_midstate(&self) -> [u8; MIDSTATE_SIZE] {
+        let mut bytes = [0; MIDSTATE_SIZE];
+        bytes[0] = midstate::VERSION;
+        bytes[1] = midstate::ALGORITHM_SHA1;
+        for (dst, word) in bytes[2..22].chunks_exact_mut(4).zip(&self.state) {
+            dst.copy_from_slice(&word.to_be_bytes());
         }
-        res
+        bytes[22..30].copy_from_slice(&self.len.to_be_bytes());
+        bytes[30] = self.block_idx as u8;
+        bytes[31..31 + self.block_idx].copy_from_slice(&self.block[..self.block_idx]);
+        bytes
+    }
+
+    /// Continues a partially computed hash from a midstate exported by [`Sha1::export_midstate`]
+    pub fn import_midstate(bytes: &[u8; MIDSTATE_SIZE]) -> Result<Self, MidstateError> {
+        midstate::check_header(bytes, midstate::ALGORITHM_SHA1)?;
+
+        let mut state = [0; 5];
+        for (word, src) in state.iter_mut().zip(bytes[2..22].chunks_exact(4)) {
+            *word = u32::from_be_bytes(src.try_into().unwrap());
+        }
+        let len = u64::from_be_bytes(bytes[22..30].try_into().unwrap());
+        let block_idx = bytes[30] as usize;
+        if block_idx as u64 != len % 64 || bytes[31 + block_idx..].iter().any(|&b| b != 0) {
+            return Err(MidstateError::Corrupted);
+        }
+
+        Ok(Self {
+            state,
+            len,
+            block_idx,
+            block: bytes[31..].try_into().unwrap(),
+        })
     }
 
     fn pad(&mut self) {
         if self.block_idx > 55 {
             // block is too small for adding padding
-            self.block[self.block_idx as usize] = 0x80;
-            for i in self.block_idx as usize + 1..64 {
+            self.block[self.block_idx] = 0x80;
+            for i in self.block_idx + 1..64 {
                 self.block[i] = 0;
             }
             self.block_idx = 64;
@@ -89,8 +171,8 @@ impl Sha1 {
             }
             self.block_idx = 56;
         } else {
-            self.block[self.block_idx as usize] = 0x80;
-            for i in self.block_idx as usize + 1..56 {
+            self.block[self.block_idx] = 0x80;
+            for i in self.block_idx + 1..56 {
                 self.block[i] = 0;
             }
             self.block_idx = 56;
@@ -103,42 +185,70 @@ impl Sha1 {
     }
 }
 
-fn compress(state: &mut [u32; 5], block: &[u8; 64]) {
-    let mut h = *state;
+const fn encode(state: &[u32; 5]) -> [u8; 20] {
+    let mut res = [0; 20];
+    let mut i = 0;
+    while i < 5 {
+        let bytes = state[i].to_be_bytes();
+        res[4 * i] = bytes[0];
+        res[4 * i + 1] = bytes[1];
+        res[4 * i + 2] = bytes[2];
+        res[4 * i + 3] = bytes[3];
+        i += 1;
+    }
+    res
+}
 
+/// Compresses `block` into `state` and wipes the intermediate values
+fn compress(state: &mut [u32; 5], block: &[u8; 64]) {
     let mut w = [0; 16];
-    for t in 0..16 {
-        w[t] = u32::from_le_bytes([
-            block[4 * t + 3],
-            block[4 * t + 2],
-            block[4 * t + 1],
+    let mut h = [0; 5];
+    compress_with(state, block, &mut w, &mut h);
+    w.zeroize();
+    h.zeroize();
+}
+
+/// Compresses `block` into `state` using `w` and `h` as scratch space for the message schedule
+/// and the working variables
+const fn compress_with(
+    state: &mut [u32; 5],
+    block: &[u8; 64],
+    w: &mut [u32; 16],
+    h: &mut [u32; 5],
+) {
+    *h = *state;
+
+    let mut t = 0;
+    while t < 16 {
+        w[t] = u32::from_be_bytes([
             block[4 * t],
+            block[4 * t + 1],
+            block[4 * t + 2],
+            block[4 * t + 3],
         ]);
+        t += 1;
     }
 
-    const K0: u32 = 0x5a827999;
-    const K1: u32 = 0x6ed9eba1;
-    const K2: u32 = 0x8f1bbcdc;
-    const K3: u32 = 0xca62c1d6;
-
-    for t in 0..16 {
-        h = step0::<_, K0>(h, &w, t, |b, c, d| (b & c) | ((!b) & d));
+    let mut t = 0;
+    while t < 16 {
+        *h = step0::<K0>(*h, w, t);
+        t += 1;
     }
-
-    for t in 16..20 {
-        h = step::<_, K0>(h, &mut w, t, |b, c, d| (b & c) | ((!b) & d));
+    while t < 20 {
+        *h = step::<K0>(*h, w, t);
+        t += 1;
     }
-
-    for t in 20..40 {
-        h = step::<_, K1>(h, &mut w, t, |b, c, d| b ^ c ^ d);
+    while t < 40 {
+        *h = step::<K1>(*h, w, t);
+        t += 1;
     }
-
-    for t in 40..60 {
-        h = step::<_, K2>(h, &mut w, t, |b, c, d| (b & c) | (b & d) | (c & d));
+    while t < 60 {
+        *h = step::<K2>(*h, w, t);
+        t += 1;
     }
-
-    for t in 60..80 {
-        h = step::<_, K3>(h, &mut w, t, |b, c, d| b ^ c ^ d);
+    while t < 80 {
+        *h = step::<K3>(*h, w, t);
+        t += 1;
     }
 
     state[0] = state[0].wrapping_add(h[0]);
@@ -148,42 +258,79 @@ fn compress(state: &mut [u32; 5], block: &[u8; 64]) {
     state[4] = state[4].wrapping_add(h[4]);
 }
 
+const K0: u32 = 0x5a827999;
+const K1: u32 = 0x6ed9eba1;
+const K2: u32 = 0x8f1bbcdc;
+const K3: u32 = 0xca62c1d6;
+
 const MASK: usize = 0xF;
 
-fn step0<F, const K: u32>([a, b, c, d, mut e]: [u32; 5], w: &[u32; 16], t: usize, f: F) -> [u32; 5]
-where
-    F: FnOnce(u32, u32, u32) -> u32,
-{
+/// Logical function of the rounds using the constant `K`
+const fn f<const K: u32>(b: u32, c: u32, d: u32) -> u32 {
+    match K {
+        K0 => (b & c) | ((!b) & d),
+        K2 => (b & c) | (b & d) | (c & d),
+        _ => b ^ c ^ d,
+    }
+}
+
+const fn step0<const K: u32>([a, b, c, d, mut e]: [u32; 5], w: &[u32; 16], t: usize) -> [u32; 5] {
     let s = t & 0xF;
     e = a
         .rotate_left(5)
-        .wrapping_add(f(b, c, d))
+        .wrapping_add(f::<K>(b, c, d))
         .wrapping_add(e)
         .wrapping_add(w[s])
         .wrapping_add(K);
     [e, a, b.rotate_left(30), c, d]
 }
 
-fn step<F, const K: u32>(
+const fn step<const K: u32>(
     [a, b, c, d, mut e]: [u32; 5],
     w: &mut [u32; 16],
     t: usize,
-    f: F,
-) -> [u32; 5]
-where
-    F: FnOnce(u32, u32, u32) -> u32,
-{
+) -> [u32; 5] {
     let s = t & 0xF;
     w[s] = (w[(s + 13) & MASK] ^ w[(s + 8) & MASK] ^ w[(s + 2) & MASK] ^ w[s]).rotate_left(1);
     e = a
         .rotate_left(5)
-        .wrapping_add(f(b, c, d))
+        .wrapping_add(f::<K>(b, c, d))
         .wrapping_add(e)
         .wrapping_add(w[s])
         .wrapping_add(K);
     [e, a, b.rotate_left(30), c, d]
 }
 
+impl Hasher for Sha1 {
+    const OUTPUT_SIZE: usize = 20;
+    const BLOCK_SIZE: usize = 64;
+
+    type Output = Secret<[u8; 20]>;
+
+    fn update(&mut self, input: &[u8]) -> &mut Self {
+        Sha1::update(self, input)
+    }
+
+    fn finalize(self) -> Self::Output {
+        self.digest()
+    }
+}
+
+impl Zeroize for Sha1 {
+    fn zeroize(&mut self) {
+        self.state.zeroize();
+        self.len.zeroize();
+        self.block_idx.zeroize();
+        self.block.zeroize();
+    }
+}
+
+impl Drop for Sha1 {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
 impl Default for Sha1 {
     fn default() -> Self {
         Self::new()
@@ -196,6 +343,8 @@ mod tests {
 
     use super::*;
 
+    use quickcheck_macros::quickcheck;
+
     #[test]
     fn test_sha1() {
         assert_eq!(
@@ -208,6 +357,84 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_const_sha1() {
+        const EMPTY: [u8; 20] = sha1_const(b"");
+        const ABC: [u8; 20] = sha1_const(b"abc");
+        const LONG: [u8; 20] = sha1_const(&[0x5a; 1000]);
+
+        assert_eq!(
+            EMPTY,
+            hex("da39a3ee5e6b4b0d3255bfef95601890afd80709").unwrap()
+        );
+        assert_eq!(
+            ABC,
+            hex("a9993e364706816aba3e25717850c26c9cd0d89d").unwrap()
+        );
+        assert_eq!(sha1(&[0x5a; 1000]), LONG);
+    }
+
+    #[quickcheck]
+    fn test_const_sha1_matches(input: Vec<u8>) {
+        assert_eq!(sha1(&input), sha1_const(&input));
+    }
+
+    #[quickcheck]
+    fn test_sha1_matches_incremental(input: Vec<u8>, split: usize) {
+        let (head, tail) = input.split_at(split % (input.len() + 1));
+        let mut state = Sha1::new();
+        state.update(head).update(tail);
+        assert_eq!(sha1(&input), state.digest());
+    }
+
+    #[quickcheck]
+    fn test_midstate(input: Vec<u8>, split: usize) {
+        let (head, tail) = input.split_at(split % (input.len() + 1));
+        let mut state = Sha1::new();
+        state.update(head);
+        let bytes = state.export_midstate();
+        drop(state);
+
+        let mut state = Sha1::import_midstate(&bytes).unwrap();
+        state.update(tail);
+        assert_eq!(state.digest(), sha1(&input));
+    }
+
+    #[test]
+    fn test_midstate_layout() {
+        let mut state = Sha1::new();
+        state.update(&[0xff; 65]);
+        let bytes = state.export_midstate();
+        assert_eq!(bytes[0..2], [1, 2]);
+        assert_eq!(bytes[22..30], 65_u64.to_be_bytes());
+        assert_eq!(bytes[30..32], [1, 0xff]);
+        assert!(bytes[32..].iter().all(|&b| b == 0));
+
+        let mut corrupted = bytes;
+        corrupted[30] = 2;
+        assert_eq!(
+            Sha1::import_midstate(&corrupted).err(),
+            Some(MidstateError::Corrupted)
+        );
+        let mut md5 = bytes;
+        md5[1] = 1;
+        assert_eq!(
+            Sha1::import_midstate(&md5).err(),
+            Some(MidstateError::WrongAlgorithm(1))
+        );
+    }
+
+    #[test]
+    fn test_zeroize() {
+        let mut state = Sha1::new();
+        state.update(b"secret");
+        state.zeroize();
+        assert_eq!(state.state, [0; 5]);
+        assert_eq!(state.len, 0);
+        assert_eq!(state.block_idx, 0);
+        assert_eq!(state.block, [0; 64]);
+    }
+
     #[test]
     fn test_sha1_1000000_updates() {
         let mut state = Sha1::new();
@@ -1,13 +1,18 @@
 //! Twofish block cipher
 //!
 //! https://www.schneier.com/academic/twofish/
-use std::convert::TryInto;
-use std::ops::{Index, Range};
+use crate::zeroize::Zeroize;
+use crate::BlockCipher;
+
+use core::convert::TryInto;
+use core::fmt;
+use core::ops::{Index, Range};
 
 const NUM_ROUNDS: usize = 16;
 const NUM_WHITENING_SUBKEYS: usize = 8;
 
-#[derive(Clone, Copy)]
+/// Twofish key, wiped on drop
+#[derive(Clone)]
 pub enum Key {
     Key128([u8; 16]),
     Key192([u8; 24]),
@@ -15,6 +20,25 @@ pub enum Key {
 }
 
 impl Key {
+    /// Creates a key from `bytes` of any length up to 32 bytes
+    ///
+    /// As defined in section 4.3 of the paper, a key shorter than 256 bits is padded with zeros
+    /// to the next of 128, 192 or 256 bits.
+    pub fn from_slice(bytes: &[u8]) -> Result<Self, InvalidKeyLength> {
+        fn padded<const N: usize>(bytes: &[u8]) -> [u8; N] {
+            let mut key = [0; N];
+            key[..bytes.len()].copy_from_slice(bytes);
+            key
+        }
+
+        match bytes.len() {
+            0..=16 => Ok(Key::Key128(padded(bytes))),
+            17..=24 => Ok(Key::Key192(padded(bytes))),
+            25..=32 => Ok(Key::Key256(padded(bytes))),
+            len => Err(InvalidKeyLength(len)),
+        }
+    }
+
     fn len_u64(&self) -> usize {
         match self {
             Key::Key128(_) => 2,
@@ -24,6 +48,22 @@ impl Key {
     }
 }
 
+impl Zeroize for Key {
+    fn zeroize(&mut self) {
+        match self {
+            Key::Key128(bytes) => bytes.zeroize(),
+            Key::Key192(bytes) => bytes.zeroize(),
+            Key::Key256(bytes) => bytes.zeroize(),
+        }
+    }
+}
+
+impl Drop for Key {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
 impl Index<usize> for Key {
     type Output = u8;
 
@@ -48,71 +88,153 @@ impl Index<Range<usize>> for Key {
     }
 }
 
-pub fn encrypt(mut data: [u8; 16], key: Key) -> [u8; 16] {
-    let schedule = expand_key(key);
+/// Error for keys longer than 256 bits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidKeyLength(pub usize);
+
+impl fmt::Display for InvalidKeyLength {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "invalid Twofish key length: {} bytes, expected at most 32 bytes",
+            self.0
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvalidKeyLength {}
 
-    // whitening with the first 4 keys
-    let mut x = [
-        get_u32(&data, 0) ^ schedule.subkeys[0],
-        get_u32(&data, 4) ^ schedule.subkeys[1],
-        get_u32(&data, 8) ^ schedule.subkeys[2],
-        get_u32(&data, 12) ^ schedule.subkeys[3],
-    ];
+pub fn encrypt(data: [u8; 16], key: Key) -> [u8; 16] {
+    Twofish::new(key).encrypt(data)
+}
 
-    let mut encrypt_round = |i0: usize, i1: usize, i2: usize, i3: usize, r: usize| {
-        let t0 = g(x[i0], schedule.sbox_keys());
-        let t1 = g(x[i1].rotate_left(8), schedule.sbox_keys());
-        let k0 = schedule.subkeys[NUM_WHITENING_SUBKEYS + 2 * r];
-        let k1 = schedule.subkeys[NUM_WHITENING_SUBKEYS + 2 * r + 1];
-        x[i3] = x[i3].rotate_left(1) ^ t0.wrapping_add(t1 << 1).wrapping_add(k1);
-        x[i2] = (x[i2] ^ t0.wrapping_add(t1).wrapping_add(k0)).rotate_right(1);
-    };
+pub fn decrypt(data: [u8; 16], key: Key) -> [u8; 16] {
+    Twofish::new(key).decrypt(data)
+}
+
+/// Twofish block cipher
+///
+/// The key is expanded once on construction, after that any number of blocks can be encrypted
+/// or decrypted. The key schedule and the key-dependent S-boxes are wiped on drop.
+///
+/// The cipher uses the "full keying" option from section 7.3 of the paper: the key-dependent
+/// S-boxes are combined with the MDS matrix into four 256-entry tables at key setup, so that the
+/// g-function is reduced to four table lookups.
+///
+/// `KEY_SIZE` is the size of a 256-bit key, however any shorter key is accepted as well and padded
+/// as described in [`Key::from_slice`].
+pub struct Twofish {
+    schedule: KeySchedule,
+    sboxes: KeyDependentSboxes,
+}
 
-    for r in 0..NUM_ROUNDS / 2 {
-        encrypt_round(0, 1, 2, 3, 2 * r);
-        encrypt_round(2, 3, 0, 1, 2 * r + 1);
+impl Twofish {
+    pub fn new(key: Key) -> Self {
+        let schedule = expand_key(key);
+        let sboxes = KeyDependentSboxes::new(schedule.sbox_keys());
+        Self { schedule, sboxes }
     }
 
-    set_u32(&mut data, 0, x[2] ^ schedule.subkeys[4]);
-    set_u32(&mut data, 4, x[3] ^ schedule.subkeys[4 + 1]);
-    set_u32(&mut data, 8, x[0] ^ schedule.subkeys[4 + 2]);
-    set_u32(&mut data, 12, x[1] ^ schedule.subkeys[4 + 3]);
+    /// Creates the cipher from a key of any length up to 32 bytes, see [`Key::from_slice`]
+    pub fn from_slice(key: &[u8]) -> Result<Self, InvalidKeyLength> {
+        Key::from_slice(key).map(Self::new)
+    }
 
-    data
-}
+    pub fn encrypt(&self, mut data: [u8; 16]) -> [u8; 16] {
+        let schedule = &self.schedule;
+        let sboxes = &self.sboxes;
 
-pub fn decrypt(mut data: [u8; 16], key: Key) -> [u8; 16] {
-    let schedule = expand_key(key);
+        // whitening with the first 4 keys
+        let mut x = [
+            get_u32(&data, 0) ^ schedule.subkeys[0],
+            get_u32(&data, 4) ^ schedule.subkeys[1],
+            get_u32(&data, 8) ^ schedule.subkeys[2],
+            get_u32(&data, 12) ^ schedule.subkeys[3],
+        ];
 
-    // whitening with the second 4 keys
-    let mut x = [
-        get_u32(&data, 0) ^ schedule.subkeys[4],
-        get_u32(&data, 4) ^ schedule.subkeys[4 + 1],
-        get_u32(&data, 8) ^ schedule.subkeys[4 + 2],
-        get_u32(&data, 12) ^ schedule.subkeys[4 + 3],
-    ];
+        let mut encrypt_round = |i0: usize, i1: usize, i2: usize, i3: usize, r: usize| {
+            let t0 = sboxes.g(x[i0]);
+            let t1 = sboxes.g(x[i1].rotate_left(8));
+            let k0 = schedule.subkeys[NUM_WHITENING_SUBKEYS + 2 * r];
+            let k1 = schedule.subkeys[NUM_WHITENING_SUBKEYS + 2 * r + 1];
+            x[i3] = x[i3].rotate_left(1) ^ t0.wrapping_add(t1 << 1).wrapping_add(k1);
+            x[i2] = (x[i2] ^ t0.wrapping_add(t1).wrapping_add(k0)).rotate_right(1);
+        };
+
+        for r in 0..NUM_ROUNDS / 2 {
+            encrypt_round(0, 1, 2, 3, 2 * r);
+            encrypt_round(2, 3, 0, 1, 2 * r + 1);
+        }
 
-    let mut decrypt_round = |i0: usize, i1: usize, i2: usize, i3: usize, r: usize| {
-        let t0 = g(x[i0], schedule.sbox_keys());
-        let t1 = g(x[i1].rotate_left(8), schedule.sbox_keys());
-        let k0 = schedule.subkeys[NUM_WHITENING_SUBKEYS + 2 * r];
-        let k1 = schedule.subkeys[NUM_WHITENING_SUBKEYS + 2 * r + 1];
-        x[i2] = x[i2].rotate_left(1) ^ t0.wrapping_add(t1).wrapping_add(k0);
-        x[i3] = (x[i3] ^ t0.wrapping_add(t1 << 1).wrapping_add(k1)).rotate_right(1);
-    };
+        set_u32(&mut data, 0, x[2] ^ schedule.subkeys[4]);
+        set_u32(&mut data, 4, x[3] ^ schedule.subkeys[4 + 1]);
+        set_u32(&mut data, 8, x[0] ^ schedule.subkeys[4 + 2]);
+        set_u32(&mut data, 12, x[1] ^ schedule.subkeys[4 + 3]);
+
+        data
+    }
+
+    pub fn decrypt(&self, mut data: [u8; 16]) -> [u8; 16] {
+        let schedule = &self.schedule;
+        let sboxes = &self.sboxes;
+
+        // whitening with the second 4 keys
+        let mut x = [
+            get_u32(&data, 0) ^ schedule.subkeys[4],
+            get_u32(&data, 4) ^ schedule.subkeys[4 + 1],
+            get_u32(&data, 8) ^ schedule.subkeys[4 + 2],
+            get_u32(&data, 12) ^ schedule.subkeys[4 + 3],
+        ];
+
+        let mut decrypt_round = |i0: usize, i1: usize, i2: usize, i3: usize, r: usize| {
+            let t0 = sboxes.g(x[i0]);
+            let t1 = sboxes.g(x[i1].rotate_left(8));
+            let k0 = schedule.subkeys[NUM_WHITENING_SUBKEYS + 2 * r];
+            let k1 = schedule.subkeys[NUM_WHITENING_SUBKEYS + 2 * r + 1];
+            x[i2] = x[i2].rotate_left(1) ^ t0.wrapping_add(t1).wrapping_add(k0);
+            x[i3] = (x[i3] ^ t0.wrapping_add(t1 << 1).wrapping_add(k1)).rotate_right(1);
+        };
+
+        for r in (0..NUM_ROUNDS / 2).rev() {
+            decrypt_round(0, 1, 2, 3, 2 * r + 1);
+            decrypt_round(2, 3, 0, 1, 2 * r);
+        }
+
+        // swap and whitening with the first 4 keys
+        set_u32(&mut data, 0, x[2] ^ schedule.subkeys[0]);
+        set_u32(&mut data, 4, x[3] ^ schedule.subkeys[1]);
+        set_u32(&mut data, 8, x[0] ^ schedule.subkeys[2]);
+        set_u32(&mut data, 12, x[1] ^ schedule.subkeys[3]);
+
+        data
+    }
+}
+
+impl Zeroize for Twofish {
+    fn zeroize(&mut self) {
+        self.schedule.zeroize();
+        self.sboxes.zeroize();
+    }
+}
+
+impl BlockCipher for Twofish {
+    const BLOCK_SIZE: usize = 16;
+    const KEY_SIZE: usize = 32;
 
-    for r in (0..NUM_ROUNDS / 2).rev() {
-        decrypt_round(0, 1, 2, 3, 2 * r + 1);
-        decrypt_round(2, 3, 0, 1, 2 * r);
+    fn new(key: &[u8]) -> Self {
+        Twofish::from_slice(key).expect("invalid key size")
     }
 
-    // swap and whitening with the first 4 keys
-    set_u32(&mut data, 0, x[2] ^ schedule.subkeys[0]);
-    set_u32(&mut data, 4, x[3] ^ schedule.subkeys[1]);
-    set_u32(&mut data, 8, x[0] ^ schedule.subkeys[2]);
-    set_u32(&mut data, 12, x[1] ^ schedule.subkeys[3]);
+    fn encrypt_block(&self, block: &mut [u8]) {
+        let ciphertext = self.encrypt((&*block).try_into().expect("invalid block size"));
+        block.copy_from_slice(&ciphertext);
+    }
 
-    data
+    fn decrypt_block(&self, block: &mut [u8]) {
+        let plaintext = self.decrypt((&*block).try_into().expect("invalid block size"));
+        block.copy_from_slice(&plaintext);
+    }
 }
 
 fn get_u32(data: &[u8; 16], idx: usize) -> u32 {
@@ -127,7 +249,6 @@ fn set_u32(data: &mut [u8; 16], idx: usize, value: u32) {
     data[idx + 3] = b[3];
 }
 
-#[allow(dead_code)]
 struct KeySchedule {
     len_u64: usize,
     sbox_keys: [u32; 4],
@@ -140,6 +261,19 @@ impl KeySchedule {
     }
 }
 
+impl Zeroize for KeySchedule {
+    fn zeroize(&mut self) {
+        self.sbox_keys.zeroize();
+        self.subkeys.zeroize();
+    }
+}
+
+impl Drop for KeySchedule {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
 fn expand_key(key: Key) -> KeySchedule {
     let mut keys_odd = [0; 4];
     let mut keys_even = [0; 4];
@@ -174,6 +308,8 @@ fn expand_key(key: Key) -> KeySchedule {
         subkeys[2 * i] = a.wrapping_add(b);
         subkeys[2 * i + 1] = a.wrapping_add(b.wrapping_mul(2)).rotate_left(SK_ROTL);
     }
+    keys_even.zeroize();
+    keys_odd.zeroize();
 
     KeySchedule {
         len_u64: key.len_u64(),
@@ -182,44 +318,80 @@ fn expand_key(key: Key) -> KeySchedule {
     }
 }
 
+/// Key-dependent S-boxes combined with the MDS matrix (full keying)
+///
+/// Entry `x` of table `i` is the MDS column `i` multiplied by the output of the key-dependent
+/// S-box `i` for the input byte `x`.
+struct KeyDependentSboxes([[u32; 256]; 4]);
+
+impl KeyDependentSboxes {
+    fn new(sbox_keys: &[u32]) -> Self {
+        let mut tables = [[0; 256]; 4];
+        for (i, table) in tables.iter_mut().enumerate() {
+            for (x, entry) in table.iter_mut().enumerate() {
+                *entry = mds_column(i, sbox(i, x as u8, sbox_keys));
+            }
+        }
+        Self(tables)
+    }
+
+    /// g-Function as defined in 4.3.3
+    fn g(&self, x: u32) -> u32 {
+        let b = x.to_le_bytes();
+        self.0[0][b[0] as usize]
+            ^ self.0[1][b[1] as usize]
+            ^ self.0[2][b[2] as usize]
+            ^ self.0[3][b[3] as usize]
+    }
+}
+
+impl Zeroize for KeyDependentSboxes {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl Drop for KeyDependentSboxes {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
 /// h-Functions as defined in 4.3.2
 fn h(x: u32, l: &[u32]) -> u32 {
-    let mut b = x.to_le_bytes();
+    let b = x.to_le_bytes();
+    (0..4).fold(0, |acc, i| acc ^ mds_column(i, sbox(i, b[i], l)))
+}
 
-    // 8x8 S-box application XOR key
+/// 8x8 S-box application XOR key for the byte `i` of the input of the h-function
+fn sbox(i: usize, mut b: u8, l: &[u32]) -> u8 {
     if l.len() == 4 {
-        let k3 = l[3].to_le_bytes();
-        b[0] = p1(b[0]) ^ k3[0];
-        b[1] = p0(b[1]) ^ k3[1];
-        b[2] = p0(b[2]) ^ k3[2];
-        b[3] = p1(b[3]) ^ k3[3];
+        let k3 = l[3].to_le_bytes()[i];
+        b = [p1, p0, p0, p1][i](b) ^ k3;
     }
     if l.len() >= 3 {
-        let k2 = l[2].to_le_bytes();
-        b[0] = p1(b[0]) ^ k2[0];
-        b[1] = p1(b[1]) ^ k2[1];
-        b[2] = p0(b[2]) ^ k2[2];
-        b[3] = p0(b[3]) ^ k2[3];
+        let k2 = l[2].to_le_bytes()[i];
+        b = [p1, p1, p0, p0][i](b) ^ k2;
+    }
+    let k0 = l[0].to_le_bytes()[i];
+    let k1 = l[1].to_le_bytes()[i];
+    match i {
+        0 => p1(p0(p0(b) ^ k1) ^ k0),
+        1 => p0(p0(p1(b) ^ k1) ^ k0),
+        2 => p1(p1(p0(b) ^ k1) ^ k0),
+        _ => p0(p1(p1(b) ^ k1) ^ k0),
     }
-    let k0 = l[0].to_le_bytes();
-    let k1 = l[1].to_le_bytes();
-    b[0] = p1(p0(p0(b[0]) ^ k1[0]) ^ k0[0]);
-    b[1] = p0(p0(p1(b[1]) ^ k1[1]) ^ k0[1]);
-    b[2] = p1(p1(p0(b[2]) ^ k1[2]) ^ k0[2]);
-    b[3] = p0(p1(p1(b[3]) ^ k1[3]) ^ k0[3]);
-
-    // MDS matrix multiplication
-    u32::from_le_bytes([
-        b[0] ^ mult_ef(b[1]) ^ mult_5b(b[2]) ^ mult_5b(b[3]),
-        mult_5b(b[0]) ^ mult_ef(b[1]) ^ mult_ef(b[2]) ^ b[3],
-        mult_ef(b[0]) ^ mult_5b(b[1]) ^ b[2] ^ mult_ef(b[3]),
-        mult_ef(b[0]) ^ b[1] ^ mult_ef(b[2]) ^ mult_5b(b[3]),
-    ])
 }
 
-/// g-Function as defined in 4.3.3
-fn g(x: u32, s: &[u32]) -> u32 {
-    h(x, s)
+/// Multiplies the column `i` of the MDS matrix with `b`
+fn mds_column(i: usize, b: u8) -> u32 {
+    let column = match i {
+        0 => [b, mult_5b(b), mult_ef(b), mult_ef(b)],
+        1 => [mult_ef(b), mult_ef(b), mult_5b(b), b],
+        2 => [mult_5b(b), mult_ef(b), b, mult_ef(b)],
+        _ => [mult_5b(b), b, mult_ef(b), mult_5b(b)],
+    };
+    u32::from_le_bytes(column)
 }
 
 fn mult_5b(x: u8) -> u8 {
@@ -371,7 +543,7 @@ const MULT_EF: [u8; 256] = [
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{hex, hex_string};
+    use crate::{hex, hex_string, hex_vec};
     use quickcheck_macros::quickcheck;
 
     #[test]
@@ -443,7 +615,7 @@ mod tests {
     }
 
     fn encrypt_decrypt_with_ciphertext(plaintext: [u8; 16], key: Key, ciphertext: [u8; 16]) {
-        let encrypted_ciphertext = encrypt(plaintext, key);
+        let encrypted_ciphertext = encrypt(plaintext, key.clone());
         assert_eq!(encrypted_ciphertext, ciphertext);
 
         let decrypted_plaintext = decrypt(ciphertext, key);
@@ -477,10 +649,134 @@ mod tests {
         );
     }
 
+    #[quickcheck]
+    fn test_full_keying_g_matches_h(x: u32, key0: u128, key1: u128) {
+        let mut key_bytes = [0; 32];
+        key_bytes[..16].copy_from_slice(&key0.to_le_bytes());
+        key_bytes[16..].copy_from_slice(&key1.to_le_bytes());
+        let schedule = expand_key(Key::Key256(key_bytes));
+        let sboxes = KeyDependentSboxes::new(schedule.sbox_keys());
+        assert_eq!(sboxes.g(x), h(x, schedule.sbox_keys()));
+    }
+
+    #[test]
+    fn test_keyed_multiple_blocks() {
+        let cipher = Twofish::new(parse_key("00000000000000000000000000000000").unwrap());
+        let plaintext = [0; 16];
+        let ciphertext = hex("9F589F5CF6122C32B6BFEC2F2AE8C35A").unwrap();
+        for _ in 0..3 {
+            assert_eq!(cipher.encrypt(plaintext), ciphertext);
+            assert_eq!(cipher.decrypt(ciphertext), plaintext);
+        }
+    }
+
+    #[test]
+    fn test_block_cipher() {
+        let keys = [
+            &hex::<16>("00000000000000000000000000000000").unwrap()[..],
+            &hex::<24>("0123456789ABCDEFFEDCBA98765432100011223344556677").unwrap()[..],
+            &hex::<32>("0123456789ABCDEFFEDCBA987654321000112233445566778899AABBCCDDEEFF").unwrap()
+                [..],
+        ];
+        let ciphertexts: [[u8; 16]; 3] = [
+            hex("9F589F5CF6122C32B6BFEC2F2AE8C35A").unwrap(),
+            hex("CFD1D2E5A9BE9CDF501F13B892BD2248").unwrap(),
+            hex("37527BE0052334B89F0CFCCAE87CFA20").unwrap(),
+        ];
+
+        for (key, ciphertext) in keys.iter().zip(ciphertexts.iter()) {
+            let cipher = <Twofish as BlockCipher>::new(key);
+            let mut block = [0; 16];
+            cipher.encrypt_block(&mut block);
+            assert_eq!(&block, ciphertext);
+            cipher.decrypt_block(&mut block);
+            assert_eq!(block, [0; 16]);
+        }
+    }
+
+    #[test]
+    fn test_intermediate_key_lengths() {
+        // keys of 1, 5, 10, 17, 20, 25 and 31 bytes; computed with a separate implementation of
+        // the Twofish paper, which reproduces the 128-, 192- and 256-bit ecb_tbl.txt vectors
+        let vectors = [
+            (
+                "81",
+                "7766E7AFC371CE232D262CAF06175E7E",
+                "F3BD671CC22DA73CACE1D9E202C8BE04",
+            ),
+            (
+                "07E7394E07",
+                "06873DF67C73880871011D72D3D75A70",
+                "4C8AFECFD36E602E7A532202991B343D",
+            ),
+            (
+                "148BC7FA770E6E057657",
+                "814891B7C36AAE8B1DCD33D52E88506A",
+                "8BFD3A78CE58FF36BD2B99481C8CD4B5",
+            ),
+            (
+                "AA9289D9EB73A66807B3DF01BDC5DD9CEF",
+                "E8F1A964FEF34BDF27B7909366597BE2",
+                "A80FE029005F9B473EEEE8D5DEAA2841",
+            ),
+            (
+                "7DB4E26CA1EFC6D6169B3BC758B158280021AA56",
+                "0BF1CD0206005AFC97D2FD94FA6434EC",
+                "6A820205120699C7A37639CDFE99762B",
+            ),
+            (
+                "BE6463CD7E9E1395BB53C80D6E755AF25751F678A35AA16E8D",
+                "2BC199C026F028F141207EE6FF445E14",
+                "C50795A48F10B32AFBD234C0833F24CC",
+            ),
+            (
+                "580A7865C0B557A35F895C1D166B3A7C8EFDD6A441A5A30490AC71A487FD31",
+                "264B03FE22159C339BE100A4B45EF34E",
+                "BC09106C621978C9D4FC8DAD57FE11A7",
+            ),
+        ];
+
+        for &(key, plaintext, ciphertext) in &vectors {
+            let cipher = Twofish::from_slice(&hex_vec(key)).unwrap();
+            let plaintext = hex(plaintext).unwrap();
+            let ciphertext = hex(ciphertext).unwrap();
+            assert_eq!(cipher.encrypt(plaintext), ciphertext);
+            assert_eq!(cipher.decrypt(ciphertext), plaintext);
+        }
+    }
+
+    #[test]
+    fn test_zeroize() {
+        let mut key = Key::from_slice(&[0xff; 32]).unwrap();
+        key.zeroize();
+        assert_eq!(key[0..32], [0; 32]);
+
+        let mut cipher = Twofish::from_slice(&[0xff; 32]).unwrap();
+        cipher.zeroize();
+        assert_eq!(cipher.schedule.sbox_keys, [0; 4]);
+        assert_eq!(cipher.schedule.subkeys, [0; 40]);
+        assert!(cipher.sboxes.0.iter().flatten().all(|&x| x == 0));
+    }
+
+    #[test]
+    fn test_key_padding() {
+        let key = Key::from_slice(&[0xAB; 5]).unwrap();
+        assert_eq!(key.len_u64(), 2);
+        assert_eq!(key[0..6], [0xAB, 0xAB, 0xAB, 0xAB, 0xAB, 0]);
+        assert_eq!(Key::from_slice(&[0; 16]).unwrap().len_u64(), 2);
+        assert_eq!(Key::from_slice(&[0; 17]).unwrap().len_u64(), 3);
+        assert_eq!(Key::from_slice(&[0; 25]).unwrap().len_u64(), 4);
+        assert_eq!(Key::from_slice(&[0; 32]).unwrap().len_u64(), 4);
+        assert_eq!(
+            Twofish::from_slice(&[0; 33]).err(),
+            Some(InvalidKeyLength(33))
+        );
+    }
+
     fn encrypt_decrypt(plaintext: &str, key: &str) {
         let plaintext = hex(plaintext).expect("invalid plaintext");
         let key = parse_key(key).expect("invalid key");
-        let decrypted_plaintext = decrypt(encrypt(plaintext, key), key);
+        let decrypted_plaintext = decrypt(encrypt(plaintext, key.clone()), key);
         assert_eq!(decrypted_plaintext, plaintext);
     }
 
@@ -0,0 +1,73 @@
+//! Wiping of key material and hash states
+//!
+//! All types holding keys, key schedules or message fragments implement [`Zeroize`] and wipe
+//! themselves on drop. The memory is overwritten with volatile writes followed by a compiler
+//! fence, so that the optimizer cannot elide the writes as dead stores.
+//!
+//! Note that copies made by moving a value, e.g. when returning it from a function, are not
+//! tracked and therefore not wiped.
+
+use core::ptr;
+use core::sync::atomic::{self, Ordering};
+
+/// Securely overwrites a value with zeros
+pub trait Zeroize {
+    fn zeroize(&mut self);
+}
+
+macro_rules! impl_zeroize_for_int {
+    ($($ty:ty),*) => {
+        $(
+            impl Zeroize for $ty {
+                fn zeroize(&mut self) {
+                    // SAFETY: `self` is a valid and aligned reference
+                    unsafe { ptr::write_volatile(self, 0) };
+                    atomic::compiler_fence(Ordering::SeqCst);
+                }
+            }
+        )*
+    };
+}
+
+impl_zeroize_for_int!(u8, u32, u64, u128, usize);
+
+impl<T: Zeroize> Zeroize for [T] {
+    fn zeroize(&mut self) {
+        for x in self {
+            x.zeroize();
+        }
+    }
+}
+
+impl<T: Zeroize, const N: usize> Zeroize for [T; N] {
+    fn zeroize(&mut self) {
+        self[..].zeroize();
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: Zeroize> Zeroize for Vec<T> {
+    fn zeroize(&mut self) {
+        self[..].zeroize();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zeroize() {
+        let mut x = 0xdeadbeef_u32;
+        x.zeroize();
+        assert_eq!(x, 0);
+
+        let mut ar = [[0xff_u8; 3]; 2];
+        ar.zeroize();
+        assert_eq!(ar, [[0; 3]; 2]);
+
+        let mut v = [1_u64, 2, 3];
+        v[1..].zeroize();
+        assert_eq!(v, [1, 0, 0]);
+    }
+}
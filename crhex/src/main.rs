@@ -1,73 +1,52 @@
-use anyhow::{anyhow, bail, Context as _};
+use anyhow::{bail, Context as _};
 use argh::FromArgs;
+use cr::encoding::HEX;
 
-use std::convert::TryInto as _;
 use std::io::{self, Read as _};
-use std::str::FromStr;
 
 #[derive(FromArgs)]
 /// Encrypt and decrypt hex strings
 struct Args {
-    /// 64bit key as hex string
+    /// key as hex string
     #[argh(option, short = 'k')]
     key: String,
-    /// algorithm to use for encryption [avalaible: des, aes128]
+    /// algorithm to use for encryption, e.g. des, aes128 or twofish
     #[argh(option, short = 'a')]
-    algorithm: Algorithm,
-}
-
-enum Algorithm {
-    Des,
-    Aes128,
-}
-
-impl FromStr for Algorithm {
-    type Err = anyhow::Error;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(match s {
-            "des" => Self::Des,
-            "aes128" => Self::Aes128,
-            _ => bail!("unknown algorithm: {}", s),
-        })
-    }
+    algorithm: String,
 }
 
 fn main() -> anyhow::Result<()> {
     let args: Args = argh::from_env();
 
-    let mut buffer = String::new();
-    io::stdin().read_to_string(&mut buffer)?;
-
-    let ciphertext = match args.algorithm {
-        Algorithm::Des => {
-            let plaintext = hex_to_u64(&buffer.trim()).context("invalid plaintext")?;
-            let key = hex_to_u64(&args.key).context("invalid key")?;
-            cr::des::encrypt(plaintext, key).to_le_bytes().to_vec()
-        }
-        Algorithm::Aes128 => {
-            let plaintext = hex_to_array(&buffer.trim()).context("invalid plaintext")?;
-            let key = hex_to_array(&args.key).context("invalid key")?;
-            cr::aes::encrypt_128(plaintext, key).to_vec()
+    let info = match cr::registry::find_cipher(&args.algorithm) {
+        Some(info) => info,
+        None => {
+            let names: Vec<_> = cr::registry::ciphers().iter().map(|c| c.name).collect();
+            bail!(
+                "unknown algorithm: {} [available: {}]",
+                args.algorithm,
+                names.join(", ")
+            );
         }
     };
 
-    println!("{}", hex::encode(&ciphertext));
+    let mut buffer = String::new();
+    io::stdin().read_to_string(&mut buffer)?;
 
-    Ok(())
-}
+    let key = HEX.decode(&args.key).context("invalid key")?;
+    let mut block = HEX.decode(buffer.trim()).context("invalid plaintext")?;
+    if block.len() != info.block_size {
+        bail!(
+            "expected a plaintext of {} bytes but it was {}",
+            info.block_size,
+            block.len()
+        );
+    }
 
-fn hex_to_array<const N: usize>(s: &str) -> anyhow::Result<[u8; N]> {
-    let bytes_vec = hex::decode(s)?;
-    bytes_vec.try_into().map_err(|v: Vec<_>| {
-        anyhow!(
-            "expected a hex string of {} bytes but it was {}",
-            N,
-            v.len()
-        )
-    })
-}
+    let cipher = info.create(&key)?;
+    cipher.encrypt_block(&mut block);
 
-fn hex_to_u64(s: &str) -> anyhow::Result<u64> {
-    hex_to_array(s).map(u64::from_le_bytes)
+    println!("{}", HEX.encode(&block));
+
+    Ok(())
 }